@@ -0,0 +1,220 @@
+//! Threshold alerts and the `--on-alert` hook
+
+use gpu_monitor_core::GpuInfo;
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Metric an alert threshold applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertMetric {
+    /// Temperature in Celsius
+    Temp,
+    /// Power usage in watts
+    Power,
+    /// GPU utilization percentage
+    Util,
+    /// Memory usage percentage
+    Mem,
+}
+
+impl AlertMetric {
+    /// Name used on the command line and in hook placeholders
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Temp => "temp",
+            Self::Power => "power",
+            Self::Util => "util",
+            Self::Mem => "mem",
+        }
+    }
+
+    /// Current value of this metric for a GPU
+    fn value(&self, gpu: &GpuInfo) -> f64 {
+        match self {
+            Self::Temp => gpu.metrics.temperature as f64,
+            Self::Power => gpu.metrics.power_watts() as f64,
+            Self::Util => gpu.metrics.gpu_utilization as f64,
+            Self::Mem => gpu.memory.usage_percent() as f64,
+        }
+    }
+}
+
+/// Alert condition: a metric at or above a threshold
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlertRule {
+    pub metric: AlertMetric,
+    pub threshold: f64,
+}
+
+/// Parse an alert rule such as `temp=85` or `power=300`
+pub fn parse_rule(s: &str) -> Result<AlertRule, String> {
+    let (metric, threshold) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid alert '{}', expected e.g. temp=85", s))?;
+    let metric = match metric.trim() {
+        "temp" => AlertMetric::Temp,
+        "power" => AlertMetric::Power,
+        "util" => AlertMetric::Util,
+        "mem" => AlertMetric::Mem,
+        other => return Err(format!("unknown alert metric '{}', expected temp, power, util or mem", other)),
+    };
+    let threshold = threshold
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid alert threshold '{}'", threshold))?;
+    Ok(AlertRule { metric, threshold })
+}
+
+/// A rule that is breached on a GPU
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Breach {
+    pub gpu: u32,
+    pub metric: AlertMetric,
+    pub value: f64,
+}
+
+/// Evaluate rules against a sample, returning every breach
+pub fn breaches(rules: &[AlertRule], gpus: &[GpuInfo]) -> Vec<Breach> {
+    gpus.iter()
+        .flat_map(|gpu| {
+            rules.iter().filter_map(move |rule| {
+                let value = rule.metric.value(gpu);
+                (value >= rule.threshold).then_some(Breach {
+                    gpu: gpu.device.index,
+                    metric: rule.metric,
+                    value,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Breach detection with debouncing and an optional hook command
+///
+/// A breach fires when it starts and then at most once per cooldown while it
+/// lasts, so a GPU sitting above a threshold does not run the hook every sample.
+pub struct Alerts {
+    rules: Vec<AlertRule>,
+    /// Command template with `{gpu}`, `{metric}` and `{value}` placeholders
+    command: Option<String>,
+    cooldown: Duration,
+    /// When each ongoing breach last fired
+    active: HashMap<(u32, AlertMetric), Instant>,
+}
+
+impl Alerts {
+    /// Create alert state for the given rules
+    pub fn new(rules: Vec<AlertRule>, command: Option<String>, cooldown: Duration) -> Self {
+        Self {
+            rules,
+            command,
+            cooldown,
+            active: HashMap::new(),
+        }
+    }
+
+    /// Check a sample, running the hook for every breach that fires
+    pub fn check(&mut self, gpus: &[GpuInfo]) {
+        for breach in self.fired(breaches(&self.rules, gpus), Instant::now()) {
+            tracing::info!(
+                gpu = breach.gpu,
+                metric = breach.metric.name(),
+                value = breach.value,
+                "alert threshold breached"
+            );
+            if let Some(command) = &self.command {
+                run_hook(command, &breach);
+            }
+        }
+    }
+
+    /// Filter the current breaches down to those that fire now
+    fn fired(&mut self, current: Vec<Breach>, now: Instant) -> Vec<Breach> {
+        // Breaches that cleared fire again as soon as they recur
+        self.active
+            .retain(|key, _| current.iter().any(|b| (b.gpu, b.metric) == *key));
+
+        current
+            .into_iter()
+            .filter(|breach| {
+                let key = (breach.gpu, breach.metric);
+                let due = self
+                    .active
+                    .get(&key)
+                    .is_none_or(|last| now.duration_since(*last) >= self.cooldown);
+                if due {
+                    self.active.insert(key, now);
+                }
+                due
+            })
+            .collect()
+    }
+}
+
+/// Substitute the breach into a hook command template
+fn expand(template: &str, breach: &Breach) -> String {
+    template
+        .replace("{gpu}", &breach.gpu.to_string())
+        .replace("{metric}", breach.metric.name())
+        .replace("{value}", &format!("{:.0}", breach.value))
+}
+
+/// Run the hook command in the background
+fn run_hook(template: &str, breach: &Breach) {
+    let command = expand(template, breach);
+
+    #[cfg(unix)]
+    let spawned = Command::new("sh").arg("-c").arg(&command).spawn();
+    #[cfg(windows)]
+    let spawned = Command::new("cmd").arg("/C").arg(&command).spawn();
+
+    match spawned {
+        // Reap the child without blocking the sampling loop
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => tracing::warn!("Failed to run alert hook '{}': {}", command, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!(
+            parse_rule("temp=85"),
+            Ok(AlertRule { metric: AlertMetric::Temp, threshold: 85.0 })
+        );
+        assert!(parse_rule("temp").is_err());
+        assert!(parse_rule("fan=50").is_err());
+        assert!(parse_rule("power=lots").is_err());
+    }
+
+    #[test]
+    fn test_debounce() {
+        let mut alerts = Alerts::new(Vec::new(), None, Duration::from_secs(60));
+        let breach = Breach { gpu: 0, metric: AlertMetric::Temp, value: 90.0 };
+        let start = Instant::now();
+
+        // Fires when the breach starts, then not again within the cooldown
+        assert_eq!(alerts.fired(vec![breach], start).len(), 1);
+        assert!(alerts.fired(vec![breach], start + Duration::from_secs(30)).is_empty());
+        assert_eq!(alerts.fired(vec![breach], start + Duration::from_secs(60)).len(), 1);
+
+        // A breach that clears fires again as soon as it recurs
+        assert!(alerts.fired(Vec::new(), start + Duration::from_secs(61)).is_empty());
+        assert_eq!(alerts.fired(vec![breach], start + Duration::from_secs(62)).len(), 1);
+    }
+
+    #[test]
+    fn test_expand() {
+        let breach = Breach { gpu: 2, metric: AlertMetric::Power, value: 312.4 };
+        assert_eq!(
+            expand("notify {gpu} {metric} {value}", &breach),
+            "notify 2 power 312"
+        );
+    }
+}
@@ -1,143 +1,579 @@
-//! TUI Application state and event loop
-
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
-use gpu_monitor_core::{GpuInfo, GpuMonitor};
-use std::time::{Duration, Instant};
-
-use crate::tui::Tui;
-use crate::ui;
-
-/// Application state
-pub struct App {
-    /// Should the application exit
-    exit: bool,
-    /// Refresh interval
-    interval: Duration,
-    /// Current GPU data
-    pub gpus: Vec<GpuInfo>,
-    /// Historical GPU usage for sparkline (last 60 samples)
-    pub gpu_history: Vec<Vec<u64>>,
-    /// Historical memory usage
-    pub memory_history: Vec<Vec<u64>>,
-    /// Last refresh time
-    last_refresh: Instant,
-    /// Current scroll position for process list
-    pub process_scroll: u16,
-}
-
-impl App {
-    /// Create a new application instance
-    pub fn new(interval_ms: u64) -> Self {
-        Self {
-            exit: false,
-            interval: Duration::from_millis(interval_ms),
-            gpus: Vec::new(),
-            gpu_history: Vec::new(),
-            memory_history: Vec::new(),
-            last_refresh: Instant::now() - Duration::from_secs(10), // Force immediate refresh
-            process_scroll: 0,
-        }
-    }
-
-    /// Run the application main loop
-    pub fn run(&mut self, terminal: &mut Tui, monitor: &GpuMonitor) -> anyhow::Result<()> {
-        while !self.exit {
-            // Refresh data if interval has passed
-            if self.last_refresh.elapsed() >= self.interval {
-                self.refresh_data(monitor)?;
-                self.last_refresh = Instant::now();
-            }
-
-            // Draw UI
-            terminal.draw(|frame| ui::draw(frame, self))?;
-
-            // Handle events with timeout
-            if event::poll(Duration::from_millis(100))? {
-                self.handle_events()?;
-            }
-        }
-
-        Ok(())
-    }
-
-    /// Refresh GPU data
-    fn refresh_data(&mut self, monitor: &GpuMonitor) -> anyhow::Result<()> {
-        self.gpus = monitor.get_all_gpu_info()?;
-
-        // Ensure history vectors are properly sized
-        while self.gpu_history.len() < self.gpus.len() {
-            self.gpu_history.push(Vec::new());
-            self.memory_history.push(Vec::new());
-        }
-
-        // Update history
-        for (i, gpu) in self.gpus.iter().enumerate() {
-            self.gpu_history[i].push(gpu.metrics.gpu_utilization as u64);
-            self.memory_history[i].push(gpu.memory.usage_percent() as u64);
-
-            // Keep last 60 samples
-            if self.gpu_history[i].len() > 60 {
-                self.gpu_history[i].remove(0);
-            }
-            if self.memory_history[i].len() > 60 {
-                self.memory_history[i].remove(0);
-            }
-        }
-
-        // Validate scroll position after data refresh
-        // If processes list shrunk, we might need to adjust scroll
-        if !self.gpus.is_empty() {
-            // For simplicity, we use the first GPU's process count as reference for scrolling
-            // In a multi-GPU scenario with independent scrolling, this would need to be per-GPU
-            let max_processes = self.gpus[0].processes.len();
-            // Assuming visible rows is roughly 10 (this is an approximation, ideally we'd get this from UI layout)
-            let visible_rows = 10;
-
-            if max_processes > visible_rows {
-                let max_scroll = (max_processes - visible_rows) as u16;
-                if self.process_scroll > max_scroll {
-                    self.process_scroll = max_scroll;
-                }
-            } else {
-                self.process_scroll = 0;
-            }
-        }
-
-        Ok(())
-    }
-
-    /// Handle keyboard events
-    fn handle_events(&mut self) -> anyhow::Result<()> {
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
-                    KeyCode::Up | KeyCode::Char('k') => {
-                        self.process_scroll = self.process_scroll.saturating_sub(1);
-                    }
-                    KeyCode::Down | KeyCode::Char('j') => {
-                        // Calculate max scroll
-                        let max_processes = if !self.gpus.is_empty() {
-                            self.gpus[0].processes.len()
-                        } else {
-                            0
-                        };
-
-                        // Approximate visible rows (this should match UI layout)
-                        // In ui.rs, the table constraint is Min(12), so roughly 10-12 rows visible
-                        let visible_rows = 10;
-
-                        if max_processes > visible_rows {
-                            let max_scroll = (max_processes - visible_rows) as u16;
-                            if self.process_scroll < max_scroll {
-                                self.process_scroll += 1;
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
-        Ok(())
-    }
-}
+//! TUI Application state and event loop
+
+use clap::ValueEnum;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use gpu_monitor_core::{GpuInfo, GpuMonitor, GpuProcess, ThrottleReason};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::alert::Alerts;
+use crate::history::HistoryFile;
+use crate::tui::Tui;
+use crate::ui;
+
+/// Approximate number of process rows visible in a GPU card
+///
+/// The card height is Min(12) in ui.rs, which leaves roughly 10 table rows.
+const VISIBLE_PROCESS_ROWS: usize = 10;
+
+/// Screen rows the merged process table does not use for process rows
+///
+/// Outer margin (2), header (3), footer (1), table borders (2), column
+/// header (1) and the selected process detail line (1), see ui.rs.
+const PROCESS_VIEW_CHROME: u16 = 10;
+
+/// Metric that can be plotted in a sparkline chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ChartMetric {
+    /// GPU utilization percentage
+    Util,
+    /// Memory usage percentage
+    Mem,
+    /// Temperature in Celsius
+    Temp,
+    /// Power usage in watts
+    Power,
+}
+
+/// How metrics are shown on each GPU card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum View {
+    /// Sparkline charts of recent history
+    Charts,
+    /// Bar gauges of the current values
+    Gauges,
+}
+
+/// Column of the process table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProcColumn {
+    /// Process ID
+    Pid,
+    /// Executable name
+    Name,
+    /// Owning user
+    User,
+    /// GPU memory in MiB
+    Mem,
+    /// GPU memory as percentage of total
+    #[value(name = "mem_pct")]
+    MemPct,
+    /// Process type (graphics, compute or both)
+    Type,
+    /// SM utilization percentage
+    #[value(name = "sm_util")]
+    SmUtil,
+    /// Full command line
+    Cmdline,
+    /// Time since the process started
+    Runtime,
+}
+
+/// Sort order of the merged process table (`processes --watch`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProcSort {
+    /// Most GPU memory first
+    #[default]
+    Memory,
+    /// Highest SM utilization first
+    SmUtil,
+    /// Longest running first
+    Runtime,
+    /// Lowest PID first
+    Pid,
+    /// Name in alphabetical order
+    Name,
+    /// GPU in display order, then most GPU memory first
+    Gpu,
+}
+
+impl ProcSort {
+    /// The next sort order when cycling with the sort key
+    pub fn next(self) -> Self {
+        match self {
+            Self::Memory => Self::SmUtil,
+            Self::SmUtil => Self::Runtime,
+            Self::Runtime => Self::Pid,
+            Self::Pid => Self::Name,
+            Self::Name => Self::Gpu,
+            Self::Gpu => Self::Memory,
+        }
+    }
+
+    /// Get a human-readable label
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Memory => "memory",
+            Self::SmUtil => "SM%",
+            Self::Runtime => "time",
+            Self::Pid => "PID",
+            Self::Name => "name",
+            Self::Gpu => "GPU",
+        }
+    }
+}
+
+/// Sort processes of all GPUs, tagged with the position of their GPU; ties go by PID
+fn sort_processes(rows: &mut [(usize, &GpuProcess)], sort: ProcSort) {
+    rows.sort_by(|(gpu_a, a), (gpu_b, b)| {
+        let order = match sort {
+            ProcSort::Memory => b.gpu_memory.cmp(&a.gpu_memory),
+            ProcSort::SmUtil => b.sm_utilization.cmp(&a.sm_utilization),
+            ProcSort::Runtime => b.runtime_seconds.cmp(&a.runtime_seconds),
+            ProcSort::Pid => std::cmp::Ordering::Equal,
+            ProcSort::Name => a.name.cmp(&b.name),
+            ProcSort::Gpu => gpu_a.cmp(gpu_b).then(b.gpu_memory.cmp(&a.gpu_memory)),
+        };
+        order.then(a.pid.cmp(&b.pid)).then(gpu_a.cmp(gpu_b))
+    });
+}
+
+/// Historical samples for a single GPU
+#[derive(Debug, Default)]
+pub struct GpuHistory {
+    /// GPU utilization percentage
+    pub util: Vec<u64>,
+    /// Memory usage percentage
+    pub memory: Vec<u64>,
+    /// Temperature in Celsius
+    pub temperature: Vec<u64>,
+    /// Power usage in watts
+    pub power: Vec<u64>,
+    /// Temperature rate of change in °C per second
+    pub temperature_rate: f32,
+    /// Power rate of change in watts per second
+    pub power_rate: f32,
+    /// Number of distinct samples seen this session
+    pub samples: u64,
+    /// Samples with clocks reduced for any reason other than idle
+    pub throttled_samples: u64,
+    /// Samples in which each throttle reason was active
+    pub throttle_histogram: HashMap<ThrottleReason, u64>,
+    /// Timestamp (unix ms), temperature and power of the previous sample
+    last: Option<(u64, f32, f32)>,
+}
+
+impl GpuHistory {
+    /// Record a new sample, keeping the last `len` values
+    fn push(&mut self, gpu: &GpuInfo, len: usize) {
+        push_sample(&mut self.util, gpu.metrics.gpu_utilization as u64, len);
+        push_sample(&mut self.memory, gpu.memory.usage_percent() as u64, len);
+        push_sample(&mut self.temperature, gpu.metrics.temperature as u64, len);
+        push_sample(&mut self.power, gpu.metrics.power_watts() as u64, len);
+
+        // A cached sample repeats the timestamp and carries no new information
+        if self.last.is_some_and(|(t, _, _)| gpu.sampled_at <= t) {
+            return;
+        }
+
+        let temperature = gpu.metrics.temperature as f32;
+        let power = gpu.metrics.power_watts();
+        if let Some((t, last_temperature, last_power)) = self.last {
+            let elapsed = gpu.sampled_at - t;
+            self.temperature_rate = rate(last_temperature, temperature, elapsed);
+            self.power_rate = rate(last_power, power, elapsed);
+        }
+        self.last = Some((gpu.sampled_at, temperature, power));
+
+        self.samples += 1;
+        let mut throttled = false;
+        for reason in gpu.throttle_reasons.iter().filter(|r| **r != ThrottleReason::GpuIdle) {
+            *self.throttle_histogram.entry(*reason).or_default() += 1;
+            throttled = true;
+        }
+        if throttled {
+            self.throttled_samples += 1;
+        }
+    }
+
+    /// Throttle reasons seen this session with their sample counts, most frequent first
+    pub fn throttle_counts(&self) -> Vec<(ThrottleReason, u64)> {
+        let mut counts: Vec<_> = self.throttle_histogram.iter().map(|(r, n)| (*r, *n)).collect();
+        counts.sort_by_key(|(reason, n)| (std::cmp::Reverse(*n), reason.label()));
+        counts
+    }
+
+    /// Get the history buffer for a chart metric
+    pub fn get(&self, metric: ChartMetric) -> &[u64] {
+        match metric {
+            ChartMetric::Util => &self.util,
+            ChartMetric::Mem => &self.memory,
+            ChartMetric::Temp => &self.temperature,
+            ChartMetric::Power => &self.power,
+        }
+    }
+}
+
+/// Row of the selected process after the process list changed
+///
+/// Follows the previously selected PID to its new row; if it exited, the
+/// previous row is clamped to the new list length.
+fn follow_selection(pids: &[u32], selected_pid: Option<u32>, previous: Option<usize>) -> Option<usize> {
+    if pids.is_empty() {
+        return None;
+    }
+    selected_pid
+        .and_then(|pid| pids.iter().position(|p| *p == pid))
+        .or_else(|| previous.map(|row| row.min(pids.len() - 1)))
+}
+
+/// Rate of change per second between two values `elapsed_ms` apart
+fn rate(previous: f32, current: f32, elapsed_ms: u64) -> f32 {
+    if elapsed_ms == 0 {
+        0.0
+    } else {
+        (current - previous) * 1000.0 / elapsed_ms as f32
+    }
+}
+
+/// Append a sample to a history buffer, dropping the oldest if full
+fn push_sample(buf: &mut Vec<u64>, value: u64, len: usize) {
+    buf.push(value);
+    if buf.len() > len {
+        buf.remove(0);
+    }
+}
+
+/// Application state
+pub struct App {
+    /// Should the application exit
+    exit: bool,
+    /// Refresh interval
+    interval: Duration,
+    /// Current GPU data
+    pub gpus: Vec<GpuInfo>,
+    /// Historical samples per GPU for sparklines
+    pub history: Vec<GpuHistory>,
+    /// Number of samples kept in history
+    history_len: usize,
+    /// Charts or gauges on each GPU card
+    pub view: View,
+    /// Metrics shown in the two sparkline charts
+    pub charts: [ChartMetric; 2],
+    /// Columns shown in the process table
+    pub proc_columns: Vec<ProcColumn>,
+    /// Interpolate between samples when the history is shorter than the chart
+    pub smooth: bool,
+    /// Keep GPUs in index order and processes in PID order
+    pub stable: bool,
+    /// Indices of the GPUs to show, all if empty
+    pub selected: Vec<u32>,
+    /// Last refresh time
+    last_refresh: Instant,
+    /// How long the last sample took to acquire from NVML
+    pub sample_latency: Option<Duration>,
+    /// Index of the GPU card whose processes are scrolled and selected
+    pub focused_gpu: usize,
+    /// Current scroll position for the focused GPU's process list
+    pub process_scroll: u16,
+    /// Row of the selected process in the focused GPU's process list
+    pub selected_process: Option<usize>,
+    /// Show one process table merged across all GPUs instead of GPU cards
+    pub process_view: bool,
+    /// Sort order of the merged process table
+    pub process_sort: ProcSort,
+    /// Process rows that fit on screen in the merged process table
+    process_view_rows: usize,
+    /// Automatic refresh is paused
+    pub paused: bool,
+    /// Refresh on the next loop iteration regardless of interval or pause
+    force_refresh: bool,
+    /// Help overlay is visible
+    pub show_help: bool,
+    /// Session statistics overlay is visible
+    pub show_stats: bool,
+    /// Emit each sample as a tracing event
+    pub trace_samples: bool,
+    /// On-disk rolling history written on every refresh
+    pub persist: Option<HistoryFile>,
+    /// Threshold alerts checked on every refresh
+    pub alerts: Option<Alerts>,
+    /// Terminal was resized since the last draw
+    resized: bool,
+}
+
+impl App {
+    /// Create a new application instance
+    pub fn new(interval_ms: u64, charts: [ChartMetric; 2], history_len: usize) -> Self {
+        Self {
+            exit: false,
+            interval: Duration::from_millis(interval_ms),
+            gpus: Vec::new(),
+            history: Vec::new(),
+            history_len,
+            view: View::Charts,
+            charts,
+            proc_columns: vec![
+                ProcColumn::Pid,
+                ProcColumn::Name,
+                ProcColumn::Mem,
+                ProcColumn::MemPct,
+                ProcColumn::Type,
+                ProcColumn::Runtime,
+            ],
+            smooth: false,
+            stable: false,
+            selected: Vec::new(),
+            last_refresh: Instant::now() - Duration::from_secs(10), // Force immediate refresh
+            sample_latency: None,
+            focused_gpu: 0,
+            process_scroll: 0,
+            selected_process: None,
+            process_view: false,
+            process_sort: ProcSort::default(),
+            process_view_rows: VISIBLE_PROCESS_ROWS,
+            paused: false,
+            force_refresh: false,
+            show_help: false,
+            show_stats: false,
+            trace_samples: false,
+            persist: None,
+            alerts: None,
+            resized: false,
+        }
+    }
+
+    /// Run the application main loop
+    pub fn run(&mut self, terminal: &mut Tui, monitor: &GpuMonitor) -> anyhow::Result<()> {
+        while !self.exit {
+            // Refresh data if interval has passed (or on request)
+            let due = !self.paused && self.last_refresh.elapsed() >= self.interval;
+            if due || self.force_refresh {
+                self.refresh_data(monitor)?;
+                self.last_refresh = Instant::now();
+                self.force_refresh = false;
+            }
+
+            // Draw UI
+            if self.process_view {
+                self.process_view_rows = terminal.size()?.height.saturating_sub(PROCESS_VIEW_CHROME).max(1) as usize;
+            }
+            terminal.draw(|frame| ui::draw(frame, self))?;
+
+            // Handle events with timeout
+            if event::poll(Duration::from_millis(100))? {
+                self.handle_events()?;
+            }
+
+            // Clear stale content so the next draw starts from the new size
+            if self.resized {
+                terminal.clear()?;
+                self.resized = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Refresh GPU data
+    fn refresh_data(&mut self, monitor: &GpuMonitor) -> anyhow::Result<()> {
+        let selected_pid = self.selected_process().map(|p| p.pid);
+        let started = Instant::now();
+        self.gpus = monitor.get_all_gpu_info()?;
+        let latency = started.elapsed();
+        self.sample_latency = Some(latency);
+        crate::select::retain(&mut self.gpus, &self.selected);
+        if self.stable {
+            crate::stabilize(&mut self.gpus);
+        }
+        if self.trace_samples {
+            crate::trace_samples(&self.gpus, latency);
+        }
+        if let Some(persist) = self.persist.as_mut() {
+            persist.record(&self.gpus)?;
+        }
+        if let Some(alerts) = self.alerts.as_mut() {
+            alerts.check(&self.gpus);
+        }
+
+        // Ensure history vectors are properly sized
+        while self.history.len() < self.gpus.len() {
+            self.history.push(GpuHistory::default());
+        }
+
+        // Update history
+        for (history, gpu) in self.history.iter_mut().zip(&self.gpus) {
+            history.push(gpu, self.history_len);
+        }
+
+        // The process list changes between samples: keep the selection on the
+        // same process if it is still running, otherwise clamp it to the list
+        self.focused_gpu = self.focused_gpu.min(self.gpus.len().saturating_sub(1));
+        self.reselect(selected_pid);
+
+        Ok(())
+    }
+
+    /// Keep the selection on a process after the rows changed, clamping the scroll position
+    fn reselect(&mut self, selected_pid: Option<u32>) {
+        let pids: Vec<u32> = self.process_rows().iter().map(|p| p.pid).collect();
+        self.selected_process = follow_selection(&pids, selected_pid, self.selected_process);
+        let max_scroll = pids.len().saturating_sub(self.visible_process_rows()) as u16;
+        self.process_scroll = self.process_scroll.min(max_scroll);
+        self.scroll_to_selection();
+    }
+
+    /// Processes of all GPUs with the GPU each runs on, in the merged table's sort order
+    pub fn merged_processes(&self) -> Vec<(&GpuInfo, &GpuProcess)> {
+        let mut rows: Vec<(usize, &GpuProcess)> = self
+            .gpus
+            .iter()
+            .enumerate()
+            .flat_map(|(position, gpu)| gpu.processes.iter().map(move |p| (position, p)))
+            .collect();
+        sort_processes(&mut rows, self.process_sort);
+        rows.into_iter().map(|(position, p)| (&self.gpus[position], p)).collect()
+    }
+
+    /// Rows of the process list being scrolled: the merged table or the focused GPU card
+    fn process_rows(&self) -> Vec<&GpuProcess> {
+        if self.process_view {
+            self.merged_processes().into_iter().map(|(_, p)| p).collect()
+        } else {
+            self.gpus.get(self.focused_gpu).map(|gpu| gpu.processes.iter().collect()).unwrap_or_default()
+        }
+    }
+
+    /// Number of process rows that fit in the process list being scrolled
+    fn visible_process_rows(&self) -> usize {
+        if self.process_view {
+            self.process_view_rows
+        } else {
+            VISIBLE_PROCESS_ROWS
+        }
+    }
+
+    /// Get the selected process of the merged table or the focused GPU
+    pub fn selected_process(&self) -> Option<&GpuProcess> {
+        self.process_rows().get(self.selected_process?).copied()
+    }
+
+    /// Move the process selection up (negative) or down, starting at the top
+    fn move_selection(&mut self, delta: isize) {
+        let count = self.process_rows().len();
+        if count == 0 {
+            return;
+        }
+        self.selected_process = Some(match self.selected_process {
+            Some(row) => row.saturating_add_signed(delta).min(count - 1),
+            None => 0,
+        });
+        self.scroll_to_selection();
+    }
+
+    /// Scroll the process list just enough to show the selected row
+    fn scroll_to_selection(&mut self) {
+        let Some(row) = self.selected_process else {
+            return;
+        };
+        let scroll = self.process_scroll as usize;
+        let visible = self.visible_process_rows();
+        if row < scroll {
+            self.process_scroll = row as u16;
+        } else if row >= scroll + visible {
+            self.process_scroll = (row + 1 - visible) as u16;
+        }
+    }
+
+    /// Handle terminal events
+    fn handle_events(&mut self) -> anyhow::Result<()> {
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_key(key.code),
+            // Layout must be recomputed for the new size before the next draw
+            Event::Resize(_, _) => self.resized = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Handle a key press
+    fn handle_key(&mut self, code: KeyCode) {
+        // Any of these keys closes the help overlay
+        if self.show_help {
+            if matches!(code, KeyCode::Char('?') | KeyCode::Esc | KeyCode::Char('q')) {
+                self.show_help = false;
+            }
+            return;
+        }
+        if self.show_stats {
+            if matches!(code, KeyCode::Char('s') | KeyCode::Esc | KeyCode::Char('q')) {
+                self.show_stats = false;
+            }
+            return;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
+            KeyCode::Char('?') => self.show_help = true,
+            KeyCode::Char('s') => self.show_stats = true,
+            KeyCode::Char('g') => {
+                self.view = match self.view {
+                    View::Charts => View::Gauges,
+                    View::Gauges => View::Charts,
+                };
+            }
+            KeyCode::Char('p') | KeyCode::Char(' ') => self.paused = !self.paused,
+            KeyCode::Char('r') => self.force_refresh = true,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Char('o') if self.process_view => {
+                let selected_pid = self.selected_process().map(|p| p.pid);
+                self.process_sort = self.process_sort.next();
+                self.reselect(selected_pid);
+            }
+            KeyCode::Tab if !self.process_view && !self.gpus.is_empty() => {
+                self.focused_gpu = (self.focused_gpu + 1) % self.gpus.len();
+                self.selected_process = None;
+                self.process_scroll = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate() {
+        // 3°C over 1.5s
+        assert_eq!(rate(70.0, 73.0, 1500), 2.0);
+        assert_eq!(rate(250.0, 200.0, 500), -100.0);
+        assert_eq!(rate(70.0, 80.0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_sort_processes() {
+        let process = |pid, name: &str, gpu_memory, sm_utilization| GpuProcess {
+            pid,
+            name: name.to_string(),
+            gpu_memory,
+            sm_utilization,
+            ..Default::default()
+        };
+        let (a, b, c) = (process(30, "python", 100, Some(90)), process(10, "xorg", 200, None), process(20, "a.out", 100, Some(5)));
+        let mut rows = vec![(0, &a), (1, &b), (0, &c)];
+        let order = |rows: &[(usize, &GpuProcess)]| rows.iter().map(|(_, p)| p.pid).collect::<Vec<_>>();
+
+        sort_processes(&mut rows, ProcSort::Memory);
+        assert_eq!(order(&rows), [10, 20, 30]);
+        sort_processes(&mut rows, ProcSort::SmUtil);
+        assert_eq!(order(&rows), [30, 20, 10]);
+        sort_processes(&mut rows, ProcSort::Name);
+        assert_eq!(order(&rows), [20, 30, 10]);
+        sort_processes(&mut rows, ProcSort::Gpu);
+        assert_eq!(order(&rows), [20, 30, 10]);
+        assert_eq!(ProcSort::Gpu.next(), ProcSort::Memory);
+    }
+
+    #[test]
+    fn test_follow_selection() {
+        // The selected process moved to another row after re-sorting
+        assert_eq!(follow_selection(&[30, 10, 20], Some(10), Some(0)), Some(1));
+        // The selected process exited: clamp to the shorter list
+        assert_eq!(follow_selection(&[30, 20], Some(40), Some(2)), Some(1));
+        assert_eq!(follow_selection(&[], Some(10), Some(0)), None);
+        assert_eq!(follow_selection(&[30], None, None), None);
+    }
+}
@@ -0,0 +1,153 @@
+//! Defaults from `~/.config/gpu-monitor/config.toml`
+//!
+//! Precedence, highest first: command-line flags, `GPU_MONITOR_*` environment
+//! variables, the config file, built-in defaults. Keys are the long flag
+//! names with `_` instead of `-`:
+//!
+//! ```toml
+//! interval = 500
+//! gpu = ["0", "rtx4090"]
+//! proc_columns = ["pid", "name", "mem", "sm_util"]
+//! alert = ["temp=85", "power=300"]
+//! ```
+
+use clap::parser::ValueSource;
+use clap::{ArgMatches, ValueEnum};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::Cli;
+
+/// Settings read from the config file, all optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub interval: Option<u64>,
+    pub history: Option<usize>,
+    pub precision: Option<u32>,
+    pub max_name_len: Option<usize>,
+    pub units: Option<String>,
+    pub view: Option<String>,
+    pub chart: Option<Vec<String>>,
+    pub proc_columns: Option<Vec<String>>,
+    pub gpu: Option<Vec<String>>,
+    pub alert: Option<Vec<String>>,
+    pub alert_cooldown: Option<String>,
+    pub load_threshold: Option<u32>,
+}
+
+impl Config {
+    /// Default config file location, honoring `XDG_CONFIG_HOME`
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(base.join("gpu-monitor").join("config.toml"))
+    }
+
+    /// Load a config file
+    ///
+    /// A missing file at the default location is not an error; a missing
+    /// file that was asked for explicitly is.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match Self::default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e)),
+            Err(e) if !explicit && e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(anyhow::anyhow!("{}: {}", path.display(), e)),
+        }
+    }
+
+    /// Parse the contents of a config file
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Fill in every setting that was not given on the command line or in the environment
+    pub fn apply(self, cli: &mut Cli, matches: &ArgMatches) -> anyhow::Result<()> {
+        let unset = |id: &str| !matches!(matches.value_source(id), Some(ValueSource::CommandLine | ValueSource::EnvVariable));
+
+        if let Some(interval) = self.interval.filter(|_| unset("interval")) {
+            cli.interval = interval;
+        }
+        if let Some(history) = self.history.filter(|_| unset("history")) {
+            cli.history = history;
+        }
+        if let Some(precision) = self.precision.filter(|_| unset("precision")) {
+            cli.precision = precision;
+        }
+        if let Some(max) = self.max_name_len.filter(|_| unset("max_name_len")) {
+            cli.max_name_len = Some(max);
+        }
+        if let Some(units) = self.units.filter(|_| unset("units")) {
+            cli.units = value_enum("units", &units)?;
+        }
+        if let Some(view) = self.view.filter(|_| unset("view")) {
+            cli.view = value_enum("view", &view)?;
+        }
+        if let Some(chart) = self.chart.filter(|_| unset("chart")) {
+            cli.chart = chart.iter().map(|c| value_enum("chart", c)).collect::<anyhow::Result<_>>()?;
+        }
+        if let Some(columns) = self.proc_columns.filter(|_| unset("proc_columns")) {
+            cli.proc_columns = columns.iter().map(|c| value_enum("proc_columns", c)).collect::<anyhow::Result<_>>()?;
+        }
+        if let Some(gpu) = self.gpu.filter(|_| unset("gpu")) {
+            cli.gpu = gpu;
+        }
+        if let Some(alert) = self.alert.filter(|_| unset("alert")) {
+            cli.alert = alert
+                .iter()
+                .map(|rule| crate::alert::parse_rule(rule).map_err(|e| anyhow::anyhow!("config alert: {}", e)))
+                .collect::<anyhow::Result<_>>()?;
+        }
+        if let Some(cooldown) = self.alert_cooldown.filter(|_| unset("alert_cooldown")) {
+            cli.alert_cooldown = crate::history::parse_duration(&cooldown)
+                .map_err(|e| anyhow::anyhow!("config alert_cooldown: {}", e))?;
+        }
+        if let Some(threshold) = self.load_threshold.filter(|_| unset("load_threshold")) {
+            cli.load_threshold = threshold;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a config value the way clap parses the flag
+fn value_enum<T: ValueEnum>(key: &str, value: &str) -> anyhow::Result<T> {
+    T::from_str(value, true).map_err(|_| {
+        let possible: Vec<_> = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value().map(|p| p.get_name().to_string()))
+            .collect();
+        anyhow::anyhow!("config {}: invalid value '{}', expected one of {}", key, value, possible.join(", "))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::ProcColumn;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse("interval = 500\nproc_columns = [\"pid\", \"sm_util\"]\n").unwrap();
+        assert_eq!(config.interval, Some(500));
+        assert!(config.gpu.is_none());
+        assert!(Config::parse("intervall = 500").is_err());
+
+        let columns: Vec<ProcColumn> = config
+            .proc_columns
+            .unwrap()
+            .iter()
+            .map(|c| value_enum("proc_columns", c).unwrap())
+            .collect();
+        assert_eq!(columns, [ProcColumn::Pid, ProcColumn::SmUtil]);
+        assert!(value_enum::<ProcColumn>("proc_columns", "gpu").is_err());
+    }
+}
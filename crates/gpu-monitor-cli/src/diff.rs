@@ -0,0 +1,189 @@
+//! Change log between consecutive samples (`--diff`)
+
+use gpu_monitor_core::{GpuInfo, ThrottleReason};
+use std::collections::HashMap;
+
+/// Metric compared between samples
+struct Field {
+    name: &'static str,
+    unit: &'static str,
+    /// Smallest change worth reporting, so sensor noise stays quiet
+    step: f64,
+    value: fn(&GpuInfo) -> f64,
+}
+
+/// Metrics reported when they move by at least their step
+const FIELDS: &[Field] = &[
+    Field {
+        name: "util",
+        unit: "%",
+        step: 5.0,
+        value: |g| g.metrics.gpu_utilization as f64,
+    },
+    Field {
+        name: "mem",
+        unit: "%",
+        step: 2.0,
+        value: |g| g.memory.usage_percent() as f64,
+    },
+    Field {
+        name: "temp",
+        unit: "°C",
+        step: 3.0,
+        value: |g| g.metrics.temperature as f64,
+    },
+    Field {
+        name: "power",
+        unit: "W",
+        step: 10.0,
+        value: |g| g.metrics.power_watts() as f64,
+    },
+];
+
+/// State of one GPU that is compared between samples
+///
+/// As the baseline, it holds the last reported values: a field only moves
+/// when its change is reported, so a slow drift is reported once it adds up
+/// to a step instead of never.
+struct Baseline {
+    values: Vec<f64>,
+    throttle: Vec<ThrottleReason>,
+    /// PID and name of each process
+    processes: Vec<(u32, String)>,
+}
+
+impl Baseline {
+    fn new(gpu: &GpuInfo) -> Self {
+        Self {
+            values: FIELDS.iter().map(|field| (field.value)(gpu)).collect(),
+            throttle: throttle_reasons(gpu),
+            processes: gpu.processes.iter().map(|p| (p.pid, p.name.clone())).collect(),
+        }
+    }
+}
+
+/// Throttle reasons worth reporting; idle comes and goes with every job
+fn throttle_reasons(gpu: &GpuInfo) -> Vec<ThrottleReason> {
+    gpu.throttle_reasons
+        .iter()
+        .copied()
+        .filter(|r| *r != ThrottleReason::GpuIdle)
+        .collect()
+}
+
+/// Reports what changed between samples, one line per GPU that changed
+#[derive(Default)]
+pub struct Differ {
+    baselines: HashMap<u32, Baseline>,
+    started: bool,
+}
+
+impl Differ {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare a sample with the last reported state, e.g. `GPU0 util 40→85%, new process python[12345]`
+    ///
+    /// The first sample only sets the baseline.
+    pub fn update(&mut self, gpus: &[GpuInfo]) -> Vec<String> {
+        let mut lines = Vec::new();
+        for gpu in gpus {
+            let index = gpu.device.index;
+            let Some(baseline) = self.baselines.get_mut(&index) else {
+                if self.started {
+                    lines.push(format!("GPU{} appeared: {}", index, gpu.device.name));
+                }
+                self.baselines.insert(index, Baseline::new(gpu));
+                continue;
+            };
+            let changes = diff(baseline, Baseline::new(gpu));
+            if !changes.is_empty() {
+                lines.push(format!("GPU{} {}", index, changes.join(", ")));
+            }
+        }
+
+        let mut gone: Vec<_> = self
+            .baselines
+            .keys()
+            .copied()
+            .filter(|index| !gpus.iter().any(|g| g.device.index == *index))
+            .collect();
+        gone.sort_unstable();
+        for index in gone {
+            self.baselines.remove(&index);
+            lines.push(format!("GPU{} is gone", index));
+        }
+
+        self.started = true;
+        lines
+    }
+}
+
+/// Changes from the last reported state to the current one, moving the baseline to what is reported
+fn diff(baseline: &mut Baseline, current: Baseline) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    for ((field, last), value) in FIELDS.iter().zip(baseline.values.iter_mut()).zip(current.values) {
+        if (value - *last).abs() >= field.step {
+            changes.push(format!("{} {:.0}→{:.0}{}", field.name, last, value, field.unit));
+            *last = value;
+        }
+    }
+
+    for reason in current.throttle.iter().filter(|r| !baseline.throttle.contains(r)) {
+        changes.push(format!("throttled by {}", reason.label()));
+    }
+    for reason in baseline.throttle.iter().filter(|r| !current.throttle.contains(r)) {
+        changes.push(format!("no longer throttled by {}", reason.label()));
+    }
+    baseline.throttle = current.throttle;
+
+    for (pid, name) in current.processes.iter().filter(|(pid, _)| !baseline.processes.iter().any(|(p, _)| p == pid)) {
+        changes.push(format!("new process {}[{}]", name, pid));
+    }
+    for (pid, name) in baseline.processes.iter().filter(|(pid, _)| !current.processes.iter().any(|(p, _)| p == pid)) {
+        changes.push(format!("process {}[{}] exited", name, pid));
+    }
+    baseline.processes = current.processes;
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let mut baseline = Baseline {
+            values: vec![40.0, 10.0, 60.0, 100.0],
+            throttle: vec![ThrottleReason::SwPowerCap],
+            processes: vec![(1, "old".to_string())],
+        };
+        let current = Baseline {
+            values: vec![85.0, 11.0, 61.0, 104.0],
+            throttle: vec![ThrottleReason::HwThermalSlowdown],
+            processes: vec![(2, "python".to_string())],
+        };
+        assert_eq!(
+            diff(&mut baseline, current),
+            [
+                "util 40→85%",
+                "throttled by hardware thermal slowdown",
+                "no longer throttled by power cap",
+                "new process python[2]",
+                "process old[1] exited",
+            ]
+        );
+
+        // Small moves add up against the last reported value
+        let drift = |power| Baseline {
+            values: vec![85.0, 11.0, 61.0, power],
+            throttle: vec![ThrottleReason::HwThermalSlowdown],
+            processes: vec![(2, "python".to_string())],
+        };
+        assert!(diff(&mut baseline, drift(106.0)).is_empty());
+        assert_eq!(diff(&mut baseline, drift(111.0)), ["power 100→111W"]);
+    }
+}
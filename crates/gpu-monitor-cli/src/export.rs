@@ -0,0 +1,491 @@
+//! Snapshot export in machine-readable formats
+
+use clap::ValueEnum;
+use gpu_monitor_core::{AggregateStats, GpuInfo};
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Export format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// JSON array of GPUs (respects --fields)
+    Json,
+    /// One CSV row per GPU with a header
+    Csv,
+    /// Prometheus text exposition format
+    Prometheus,
+    /// InfluxDB line protocol
+    Influx,
+}
+
+/// Numeric metric included in the flat export formats
+struct Metric {
+    /// Name, prefixed with `gpu_` in Prometheus
+    name: &'static str,
+    /// Description for the Prometheus `# HELP` line
+    help: &'static str,
+    /// Value for a GPU, None if not available
+    value: fn(&GpuInfo) -> Option<f64>,
+}
+
+/// Metrics exported by the CSV, Prometheus and Influx formats
+const METRICS: &[Metric] = &[
+    Metric {
+        name: "utilization_percent",
+        help: "GPU utilization",
+        value: |g| Some(g.metrics.gpu_utilization as f64),
+    },
+    Metric {
+        name: "memory_utilization_percent",
+        help: "Memory controller utilization",
+        value: |g| Some(g.metrics.memory_utilization as f64),
+    },
+    Metric {
+        name: "memory_used_bytes",
+        help: "Memory in use",
+        value: |g| Some(g.memory.used as f64),
+    },
+    Metric {
+        name: "memory_total_bytes",
+        help: "Total memory",
+        value: |g| Some(g.memory.total as f64),
+    },
+    Metric {
+        name: "temperature_celsius",
+        help: "GPU temperature",
+        value: |g| Some(g.metrics.temperature as f64),
+    },
+    Metric {
+        name: "power_watts",
+        help: "Power draw",
+        value: |g| Some(g.metrics.power_watts() as f64),
+    },
+    Metric {
+        name: "fan_speed_percent",
+        help: "Fan speed",
+        value: |g| g.metrics.fan_speed.filter(|_| g.device.has_fan).map(|f| f as f64),
+    },
+    Metric {
+        name: "clock_graphics_mhz",
+        help: "Graphics clock",
+        value: |g| Some(g.metrics.clock_graphics as f64),
+    },
+    Metric {
+        name: "clock_memory_mhz",
+        help: "Memory clock",
+        value: |g| Some(g.metrics.clock_memory as f64),
+    },
+    Metric {
+        name: "encoder_utilization_percent",
+        help: "Video encoder utilization",
+        value: |g| g.encoder_utilization().map(|u| u as f64),
+    },
+    Metric {
+        name: "decoder_utilization_percent",
+        help: "Video decoder utilization",
+        value: |g| g.decoder_utilization().map(|u| u as f64),
+    },
+    Metric {
+        name: "ofa_utilization_percent",
+        help: "Optical flow accelerator utilization",
+        value: |g| g.metrics.ofa_utilization.map(|u| u as f64),
+    },
+    Metric {
+        name: "jpeg_utilization_percent",
+        help: "JPEG decoder utilization",
+        value: |g| g.metrics.jpeg_utilization.map(|u| u as f64),
+    },
+    Metric {
+        name: "pcie_replay_count",
+        help: "PCIe replays since the driver loaded",
+        value: |g| g.metrics.pcie_replay_count.map(|c| c as f64),
+    },
+];
+
+/// How GPUs are serialized to JSON
+#[derive(Debug, Clone, Copy)]
+pub struct JsonOptions {
+    /// Decimal places of the derived GiB and watts values
+    pub precision: u32,
+    /// Longest process name or command line in characters, unlimited if None
+    pub max_name_len: Option<usize>,
+    /// Emit numeric fields that have a unit as `{ "value": 61, "unit": "C" }`
+    pub with_units: bool,
+}
+
+/// Unit of each numeric JSON field, by dotted path without array indices
+///
+/// `.*` matches any key of a map. Numbers without a unit (indices, IDs,
+/// counts) are never wrapped.
+const UNITS: &[(&str, &str)] = &[
+    ("device.power_limit", "W"),
+    ("device.power_limit_max", "W"),
+    ("device.temp_slowdown", "C"),
+    ("device.temp_shutdown", "C"),
+    ("device.temp_memory_max", "C"),
+    ("device.clock_range.graphics_min", "MHz"),
+    ("device.clock_range.graphics_max", "MHz"),
+    ("device.clock_range.memory_min", "MHz"),
+    ("device.clock_range.memory_max", "MHz"),
+    ("device.app_clock_graphics", "MHz"),
+    ("device.app_clock_memory", "MHz"),
+    ("device.default_app_clock_graphics", "MHz"),
+    ("device.default_app_clock_memory", "MHz"),
+    ("device.clock_graphics_base", "MHz"),
+    ("device.clock_memory_base", "MHz"),
+    ("device.boost_clock_rated", "MHz"),
+    ("metrics.gpu_utilization", "%"),
+    ("metrics.memory_utilization", "%"),
+    ("metrics.encoder_utilization", "%"),
+    ("metrics.decoder_utilization", "%"),
+    ("metrics.encoder_sampling_us", "us"),
+    ("metrics.decoder_sampling_us", "us"),
+    ("metrics.ofa_utilization", "%"),
+    ("metrics.jpeg_utilization", "%"),
+    ("metrics.temperature", "C"),
+    ("metrics.temperatures.*", "C"),
+    ("metrics.power_usage", "mW"),
+    ("metrics.power_watts", "W"),
+    ("metrics.fan_speed", "%"),
+    ("metrics.fan_speeds", "%"),
+    ("metrics.clock_graphics", "MHz"),
+    ("metrics.clock_memory", "MHz"),
+    ("metrics.clock_sm", "MHz"),
+    ("memory.total", "B"),
+    ("memory.used", "B"),
+    ("memory.free", "B"),
+    ("memory.reserved", "B"),
+    ("memory.ecc_reserved", "B"),
+    ("memory.bar1_total", "B"),
+    ("memory.bar1_used", "B"),
+    ("memory.overhead", "B"),
+    ("memory.used_gib", "GiB"),
+    ("memory.total_gib", "GiB"),
+    ("memory.physical_total_gib", "GiB"),
+    ("mig_instances.memory_total", "B"),
+    ("processes.gpu_memory", "B"),
+    ("processes.sm_utilization", "%"),
+    ("processes.runtime_seconds", "s"),
+    ("sampled_at", "ms"),
+    // `--summary`
+    ("avg_utilization", "%"),
+    ("total_memory_used", "B"),
+    ("total_memory", "B"),
+    ("total_power", "W"),
+    ("max_temperature", "C"),
+];
+
+/// Render a snapshot of all GPUs in the given format
+pub fn export(
+    gpus: &[GpuInfo],
+    format: ExportFormat,
+    fields: &[String],
+    options: JsonOptions,
+) -> anyhow::Result<String> {
+    Ok(match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&gpus_to_json(gpus, fields, options)?)? + "\n",
+        ExportFormat::Csv => to_csv(gpus),
+        ExportFormat::Prometheus => to_prometheus(gpus),
+        ExportFormat::Influx => to_influx(gpus),
+    })
+}
+
+/// Serialize GPU info, keeping only the selected fields if any are given
+pub fn gpus_to_json(gpus: &[GpuInfo], fields: &[String], options: JsonOptions) -> anyhow::Result<Value> {
+    let gpus = gpus
+        .iter()
+        .map(|gpu| {
+            let value = gpu_to_json(gpu, options)?;
+            Ok(if fields.is_empty() { value } else { project_fields(&value, fields) })
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Value::Array(gpus))
+}
+
+/// Serialize a GPU with memory in GiB and power in watts added
+///
+/// The derived values are rounded to `precision` decimals so they print as
+/// `6.1` rather than the `6.099999904632568` an `f32` widens to. Process
+/// names and command lines are cut to `max_name_len` characters.
+pub fn gpu_to_json(gpu: &GpuInfo, options: JsonOptions) -> anyhow::Result<Value> {
+    let precision = options.precision;
+    let mut value = serde_json::to_value(gpu)?;
+    if let Some(memory) = value.get_mut("memory").and_then(Value::as_object_mut) {
+        memory.insert("used_gib".into(), round(gpu.memory.used_gib(), precision).into());
+        memory.insert("total_gib".into(), round(gpu.memory.total_gib(), precision).into());
+        let physical_gib = gpu.memory.physical_total() as f32 / (1024.0 * 1024.0 * 1024.0);
+        memory.insert("physical_total_gib".into(), round(physical_gib, precision).into());
+        memory.insert("overhead".into(), gpu.memory_overhead().into());
+    }
+    if let Some(metrics) = value.get_mut("metrics").and_then(Value::as_object_mut) {
+        metrics.insert("power_watts".into(), round(gpu.metrics.power_watts(), precision).into());
+    }
+    if let Some(object) = value.as_object_mut() {
+        object.insert("healthy".into(), gpu.is_healthy().into());
+    }
+    if let Some(max) = options.max_name_len {
+        for process in value.get_mut("processes").and_then(Value::as_array_mut).into_iter().flatten() {
+            for key in ["name", "cmdline"] {
+                if let Some(Value::String(text)) = process.get_mut(key) {
+                    *text = truncate_name(text, max);
+                }
+            }
+        }
+    }
+    if options.with_units {
+        add_units(&mut value, "");
+    }
+    Ok(value)
+}
+
+/// Wrap every numeric field listed in [`UNITS`] as `{ "value": ..., "unit": ... }`
+fn add_units(value: &mut Value, path: &str) {
+    match value {
+        Value::Object(object) => {
+            for (key, field) in object.iter_mut() {
+                let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                add_units(field, &path);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                add_units(item, path);
+            }
+        }
+        Value::Number(_) => {
+            if let Some(unit) = unit_of(path) {
+                *value = serde_json::json!({ "value": value.take(), "unit": unit });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Look up the unit of a field path in [`UNITS`]
+fn unit_of(path: &str) -> Option<&'static str> {
+    UNITS
+        .iter()
+        .find(|(pattern, _)| match pattern.strip_suffix(".*") {
+            Some(prefix) => path.strip_prefix(prefix).is_some_and(|key| key.starts_with('.')),
+            None => *pattern == path,
+        })
+        .map(|(_, unit)| *unit)
+}
+
+/// Cut a string to at most `max` characters, ending in `…` when shortened
+///
+/// Counts characters rather than bytes so multi-byte names are never split
+/// mid-character.
+pub fn truncate_name(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max.saturating_sub(1)).collect();
+    if max > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
+/// Serialize fleet totals, rounding the float fields like `gpu_to_json`
+pub fn summary_to_json(stats: &AggregateStats, options: JsonOptions) -> anyhow::Result<Value> {
+    let mut value = serde_json::to_value(stats)?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert("avg_utilization".into(), round(stats.avg_utilization, options.precision).into());
+        object.insert("total_power".into(), round(stats.total_power, options.precision).into());
+    }
+    if options.with_units {
+        add_units(&mut value, "");
+    }
+    Ok(value)
+}
+
+/// Round a value to the given number of decimals
+fn round(value: f32, decimals: u32) -> f64 {
+    let scale = 10f64.powi(decimals as i32);
+    (value as f64 * scale).round() / scale
+}
+
+/// Build a flat object with the selected fields of a GPU
+///
+/// A field is either a dotted path (`metrics.temperature`), a top-level key
+/// (`processes`), or a key inside one of the nested objects (`temperature`).
+/// Unknown fields are emitted as `null` so the output shape stays stable.
+pub fn project_fields(gpu: &Value, fields: &[String]) -> Value {
+    let mut out = serde_json::Map::new();
+    for field in fields {
+        let value = lookup_path(gpu, field)
+            .or_else(|| {
+                gpu.as_object()?
+                    .values()
+                    .find_map(|nested| nested.as_object()?.get(field.as_str()))
+            })
+            .cloned()
+            .unwrap_or(Value::Null);
+        out.insert(field.clone(), value);
+    }
+    Value::Object(out)
+}
+
+/// Look up a dotted path in a JSON value
+fn lookup_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |v, key| v.get(key))
+}
+
+/// One row per GPU, empty cells for unavailable values
+fn to_csv(gpus: &[GpuInfo]) -> String {
+    let mut out = String::from("index,uuid,name");
+    for metric in METRICS {
+        out.push(',');
+        out.push_str(metric.name);
+    }
+    out.push('\n');
+
+    for gpu in gpus {
+        let _ = write!(out, "{},{},{}", gpu.device.index, gpu.device.uuid, csv_field(&gpu.device.name));
+        for metric in METRICS {
+            out.push(',');
+            if let Some(value) = (metric.value)(gpu) {
+                let _ = write!(out, "{}", value);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Gauges labeled by GPU index, UUID and name
+fn to_prometheus(gpus: &[GpuInfo]) -> String {
+    let mut out = String::new();
+    for metric in METRICS {
+        let _ = writeln!(out, "# HELP gpu_{} {}", metric.name, metric.help);
+        let _ = writeln!(out, "# TYPE gpu_{} gauge", metric.name);
+        for gpu in gpus {
+            if let Some(value) = (metric.value)(gpu) {
+                let _ = writeln!(
+                    out,
+                    "gpu_{}{{index=\"{}\",uuid=\"{}\",name=\"{}\"}} {}",
+                    metric.name,
+                    gpu.device.index,
+                    prometheus_label(&gpu.device.uuid),
+                    prometheus_label(&gpu.device.name),
+                    value
+                );
+            }
+        }
+    }
+    out
+}
+
+/// Escape a Prometheus label value
+fn prometheus_label(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// One `gpu` point per GPU, timestamped with the sample time in nanoseconds
+fn to_influx(gpus: &[GpuInfo]) -> String {
+    let mut out = String::new();
+    for gpu in gpus {
+        let fields: Vec<String> = METRICS
+            .iter()
+            .filter_map(|metric| Some(format!("{}={}", metric.name, (metric.value)(gpu)?)))
+            .collect();
+        let _ = writeln!(
+            out,
+            "gpu,index={},uuid={},name={} {} {}",
+            gpu.device.index,
+            influx_tag(&gpu.device.uuid),
+            influx_tag(&gpu.device.name),
+            fields.join(","),
+            gpu.sampled_at * 1_000_000
+        );
+    }
+    out
+}
+
+/// Escape an InfluxDB tag value
+fn influx_tag(s: &str) -> String {
+    s.replace(',', "\\,").replace('=', "\\=").replace(' ', "\\ ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_units() {
+        let mut gpu = serde_json::json!({
+            "device": { "index": 0 },
+            "metrics": { "temperature": 61, "temperatures": { "memory": 70 }, "fan_speeds": [40, 42] },
+            "processes": [{ "pid": 7, "gpu_memory": 1024 }]
+        });
+        add_units(&mut gpu, "");
+        assert_eq!(
+            gpu,
+            serde_json::json!({
+                "device": { "index": 0 },
+                "metrics": {
+                    "temperature": { "value": 61, "unit": "C" },
+                    "temperatures": { "memory": { "value": 70, "unit": "C" } },
+                    "fan_speeds": [{ "value": 40, "unit": "%" }, { "value": 42, "unit": "%" }]
+                },
+                "processes": [{ "pid": 7, "gpu_memory": { "value": 1024, "unit": "B" } }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_truncate_name() {
+        assert_eq!(truncate_name("python", 10), "python");
+        assert_eq!(truncate_name("python3.11", 6), "pytho…");
+        assert_eq!(truncate_name("日本語のプロセス", 4), "日本語…");
+        assert_eq!(truncate_name("python", 0), "");
+    }
+
+    #[test]
+    fn test_project_fields() {
+        let gpu = serde_json::json!({
+            "device": { "index": 0, "name": "Test GPU" },
+            "metrics": { "gpu_utilization": 42, "temperature": 65 },
+            "sampled_at": 1000
+        });
+        let fields: Vec<String> = ["index", "metrics.temperature", "sampled_at", "missing"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(
+            project_fields(&gpu, &fields),
+            serde_json::json!({
+                "index": 0,
+                "metrics.temperature": 65,
+                "sampled_at": 1000,
+                "missing": null
+            })
+        );
+    }
+
+    #[test]
+    fn test_round() {
+        assert_eq!(serde_json::json!(round(6.1, 2)).to_string(), "6.1");
+        assert_eq!(round(245.678, 2), 245.68);
+        assert_eq!(round(245.678, 0), 246.0);
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(csv_field("RTX 4090"), "RTX 4090");
+        assert_eq!(csv_field("A, \"B\""), "\"A, \"\"B\"\"\"");
+        assert_eq!(prometheus_label("a\"b\\c"), "a\\\"b\\\\c");
+        assert_eq!(influx_tag("NVIDIA A100, 80GB=x"), "NVIDIA\\ A100\\,\\ 80GB\\=x");
+    }
+}
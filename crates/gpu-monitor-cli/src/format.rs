@@ -0,0 +1,177 @@
+//! Number and unit formatting for plain text output
+
+use clap::ValueEnum;
+
+/// Unit used to display memory sizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MemoryUnit {
+    /// Binary gigabytes (1024³ bytes)
+    Gib,
+    /// Decimal gigabytes (10⁹ bytes), as on vendor spec sheets
+    Gb,
+    /// Binary megabytes (1024² bytes)
+    Mib,
+}
+
+impl MemoryUnit {
+    /// Unit label for display
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Gib => "GiB",
+            Self::Gb => "GB",
+            Self::Mib => "MiB",
+        }
+    }
+
+    /// Convert a byte count to this unit
+    pub fn convert(&self, bytes: u64) -> f64 {
+        match self {
+            Self::Gib => bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+            Self::Gb => bytes as f64 / 1_000_000_000.0,
+            Self::Mib => bytes as f64 / (1024.0 * 1024.0),
+        }
+    }
+}
+
+/// Locale-dependent number separators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Thousands separator
+    pub thousands: char,
+    /// Decimal separator
+    pub decimal: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            thousands: ',',
+            decimal: '.',
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Detect separators from the `LC_ALL`, `LC_NUMERIC` or `LANG` environment variables
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map(|locale| Self::for_locale(&locale))
+            .unwrap_or_default()
+    }
+
+    /// Get separators for a locale name such as `de_DE.UTF-8`
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale.split(['_', '.', '@']).next().unwrap_or("");
+        match language {
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" => Self {
+                thousands: '.',
+                decimal: ',',
+            },
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "uk" | "hu" | "bg" => Self {
+                thousands: ' ',
+                decimal: ',',
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// Format an integer with thousands separators
+    pub fn integer(&self, value: u64) -> String {
+        let digits = value.to_string();
+        let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(self.thousands);
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    /// Format a decimal number with the given precision
+    pub fn decimal(&self, value: f64, precision: usize) -> String {
+        let formatted = format!("{:.*}", precision, value);
+        match formatted.split_once('.') {
+            Some((int, frac)) => {
+                let int = int.parse().map(|i| self.integer(i)).unwrap_or_else(|_| int.to_string());
+                format!("{}{}{}", int, self.decimal, frac)
+            }
+            None => formatted
+                .parse()
+                .map(|i| self.integer(i))
+                .unwrap_or(formatted),
+        }
+    }
+
+    /// Format a byte count in the given unit (without the unit label)
+    pub fn memory(&self, bytes: u64, unit: MemoryUnit) -> String {
+        match unit {
+            MemoryUnit::Mib => self.integer(unit.convert(bytes) as u64),
+            _ => self.decimal(unit.convert(bytes), 1),
+        }
+    }
+}
+
+/// Format a duration in seconds compactly with its two largest units, e.g. `2d3h`, `4h12m`, `15s`
+pub fn duration(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86_400, seconds / 3600 % 24, seconds / 60 % 60);
+    let (major, minor) = if days > 0 {
+        ((days, 'd'), (hours, 'h'))
+    } else if hours > 0 {
+        ((hours, 'h'), (minutes, 'm'))
+    } else if minutes > 0 {
+        ((minutes, 'm'), (seconds % 60, 's'))
+    } else {
+        return format!("{}s", seconds);
+    };
+    if minor.0 == 0 {
+        format!("{}{}", major.0, major.1)
+    } else {
+        format!("{}{}{}{}", major.0, major.1, minor.0, minor.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_format() {
+        let en = NumberFormat::for_locale("en_US.UTF-8");
+        assert_eq!(en.integer(1234567), "1,234,567");
+        assert_eq!(en.integer(999), "999");
+        assert_eq!(en.decimal(1234.5, 1), "1,234.5");
+
+        let de = NumberFormat::for_locale("de_DE.UTF-8");
+        assert_eq!(de.integer(24576), "24.576");
+        assert_eq!(de.decimal(7.25, 2), "7,25");
+
+        assert_eq!(NumberFormat::for_locale("C"), NumberFormat::default());
+    }
+
+    #[test]
+    fn test_memory_units() {
+        let fmt = NumberFormat::default();
+        let bytes = 8 * 1024 * 1024 * 1024;
+        assert_eq!(fmt.memory(bytes, MemoryUnit::Gib), "8.0");
+        assert_eq!(fmt.memory(bytes, MemoryUnit::Gb), "8.6");
+        assert_eq!(fmt.memory(bytes, MemoryUnit::Mib), "8,192");
+    }
+
+    #[test]
+    fn test_duration() {
+        assert_eq!(duration(0), "0s");
+        assert_eq!(duration(15), "15s");
+        assert_eq!(duration(59), "59s");
+        assert_eq!(duration(60), "1m");
+        assert_eq!(duration(192), "3m12s");
+        assert_eq!(duration(3600), "1h");
+        assert_eq!(duration(15_120), "4h12m");
+        assert_eq!(duration(183_600), "2d3h");
+        assert_eq!(duration(172_800 + 59), "2d");
+        assert_eq!(duration(400 * 86_400), "400d");
+    }
+}
@@ -0,0 +1,153 @@
+//! Rolling sample history persisted to disk as NDJSON
+
+use gpu_monitor_core::GpuInfo;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// NDJSON file holding the samples of the last `window`
+///
+/// Each line is `{"sampled_at": <unix ms>, "gpus": [...]}`. New samples are
+/// appended, so the file survives the monitor crashing. Samples that fell
+/// out of the window stay at the start of the file until they outnumber
+/// the rest; then the file is rewritten without them. Readers should skip
+/// lines older than the window.
+pub struct HistoryFile {
+    path: PathBuf,
+    window: Duration,
+    /// Timestamp and line of every sample still in the window
+    lines: VecDeque<(u64, String)>,
+    /// Lines in the file, including expired ones not yet compacted away
+    file_lines: usize,
+}
+
+impl HistoryFile {
+    /// Open a history file, keeping samples from a previous run that are still in the window
+    pub fn open(path: PathBuf, window: Duration) -> anyhow::Result<Self> {
+        let lines: VecDeque<_> = match fs::read_to_string(&path) {
+            Ok(content) => content
+                .lines()
+                .filter_map(|line| {
+                    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+                    Some((value.get("sampled_at")?.as_u64()?, line.to_string()))
+                })
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => VecDeque::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let file_lines = lines.len();
+        let mut history = Self { path, window, lines, file_lines };
+        history.expire(now);
+        // Drops expired samples and any unparsable lines left by a crash mid-write
+        history.rewrite()?;
+        Ok(history)
+    }
+
+    /// Record a sample, dropping samples older than the window
+    pub fn record(&mut self, gpus: &[GpuInfo]) -> anyhow::Result<()> {
+        let Some(sampled_at) = gpus.first().map(|g| g.sampled_at) else {
+            return Ok(());
+        };
+        let line = serde_json::to_string(&serde_json::json!({
+            "sampled_at": sampled_at,
+            "gpus": gpus,
+        }))?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        self.lines.push_back((sampled_at, line));
+        self.file_lines += 1;
+
+        self.expire(sampled_at);
+        if self.needs_compaction() {
+            self.rewrite()?;
+        }
+        Ok(())
+    }
+
+    /// Drop samples older than the window before `now`, returning whether any were dropped
+    fn expire(&mut self, now: u64) -> bool {
+        let cutoff = now.saturating_sub(self.window.as_millis() as u64);
+        let before = self.lines.len();
+        while self.lines.front().is_some_and(|(t, _)| *t < cutoff) {
+            self.lines.pop_front();
+        }
+        self.lines.len() != before
+    }
+
+    /// Check if expired lines make up more than half of the file
+    fn needs_compaction(&self) -> bool {
+        self.file_lines > 2 * self.lines.len()
+    }
+
+    /// Replace the file with the samples in memory
+    fn rewrite(&mut self) -> anyhow::Result<()> {
+        // Write to a temporary file first so a crash never leaves a partial history
+        let tmp = self.path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        for (_, line) in &self.lines {
+            writeln!(file, "{}", line)?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        self.file_lines = self.lines.len();
+        Ok(())
+    }
+}
+
+/// Parse a duration such as `90s`, `5m` or `1h` (plain numbers are seconds)
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let value: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}', expected e.g. 30s, 5m or 1h", s))?;
+    let seconds = match unit {
+        "" | "s" => value,
+        "m" => value * 60,
+        "h" => value * 3600,
+        _ => return Err(format!("unknown duration unit '{}', expected s, m or h", unit)),
+    };
+    Ok(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+
+    #[test]
+    fn test_expire() {
+        let mut history = HistoryFile {
+            path: PathBuf::new(),
+            window: Duration::from_secs(60),
+            lines: (0..5).map(|i| (i * 30_000, String::new())).collect(),
+            file_lines: 5,
+        };
+
+        // Samples at 0s, 30s, 60s, 90s and 120s; keep the last minute
+        assert!(history.expire(120_000));
+        assert_eq!(history.lines.front().map(|(t, _)| *t), Some(60_000));
+        assert!(!history.expire(120_000));
+
+        // Two expired lines of five are left in the file until they are the majority
+        assert!(!history.needs_compaction());
+        assert!(history.expire(150_000));
+        assert!(history.needs_compaction());
+    }
+}
@@ -0,0 +1,243 @@
+//! Sustained load detection and per-run summaries (`--detect-load`)
+
+use gpu_monitor_core::GpuInfo;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+/// The metrics of one GPU sample that load detection looks at
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Sample {
+    gpu: u32,
+    /// Unix timestamp in milliseconds
+    at: u64,
+    utilization: u32,
+    temperature: u32,
+    /// Power in watts
+    power: f32,
+}
+
+impl From<&GpuInfo> for Sample {
+    fn from(gpu: &GpuInfo) -> Self {
+        Self {
+            gpu: gpu.device.index,
+            at: gpu.sampled_at,
+            utilization: gpu.metrics.gpu_utilization,
+            temperature: gpu.metrics.temperature,
+            power: gpu.metrics.power_watts(),
+        }
+    }
+}
+
+/// Statistics accumulated over one period of load
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadSummary {
+    pub gpu: u32,
+    /// Unix timestamp (ms) of the first loaded sample
+    pub started_at: u64,
+    /// Unix timestamp (ms) of the last loaded sample
+    pub ended_at: u64,
+    samples: u32,
+    temperature_sum: f64,
+    pub temperature_peak: u32,
+    power_sum: f64,
+    pub power_peak: f32,
+    /// Energy consumed in joules
+    pub energy_joules: f64,
+    /// Timestamp (ms) and power of the previous sample for energy integration
+    last: (u64, f32),
+}
+
+impl LoadSummary {
+    /// Start a summary from the first loaded sample
+    fn new(sample: Sample) -> Self {
+        Self {
+            gpu: sample.gpu,
+            started_at: sample.at,
+            ended_at: sample.at,
+            samples: 1,
+            temperature_sum: sample.temperature as f64,
+            temperature_peak: sample.temperature,
+            power_sum: sample.power as f64,
+            power_peak: sample.power,
+            energy_joules: 0.0,
+            last: (sample.at, sample.power),
+        }
+    }
+
+    /// Add a loaded sample
+    fn add(&mut self, sample: Sample) {
+        let (last_at, last_power) = self.last;
+        // Trapezoidal integration between consecutive samples
+        let elapsed = sample.at.saturating_sub(last_at) as f64 / 1000.0;
+        self.energy_joules += (last_power + sample.power) as f64 / 2.0 * elapsed;
+
+        self.ended_at = sample.at;
+        self.samples += 1;
+        self.temperature_sum += sample.temperature as f64;
+        self.temperature_peak = self.temperature_peak.max(sample.temperature);
+        self.power_sum += sample.power as f64;
+        self.power_peak = self.power_peak.max(sample.power);
+        self.last = (sample.at, sample.power);
+    }
+
+    /// Time between the first and last loaded sample
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.ended_at - self.started_at)
+    }
+
+    /// Average temperature in Celsius
+    pub fn temperature_avg(&self) -> f64 {
+        self.temperature_sum / self.samples as f64
+    }
+
+    /// Average power in watts
+    pub fn power_avg(&self) -> f64 {
+        self.power_sum / self.samples as f64
+    }
+}
+
+impl fmt::Display for LoadSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GPU {}: load ended after {}, temp avg {:.0}°C peak {}°C, power avg {:.1} W peak {:.1} W, energy {:.1} kJ",
+            self.gpu,
+            crate::format::duration(self.duration().as_secs()),
+            self.temperature_avg(),
+            self.temperature_peak,
+            self.power_avg(),
+            self.power_peak,
+            self.energy_joules / 1000.0
+        )
+    }
+}
+
+/// Change in a GPU's load state
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadEvent {
+    /// Load stayed above the threshold for the minimum duration
+    Started { gpu: u32, since: u64 },
+    /// Sustained load dropped below the threshold
+    Ended(LoadSummary),
+}
+
+impl fmt::Display for LoadEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Started { gpu, .. } => write!(f, "GPU {}: sustained load started", gpu),
+            Self::Ended(summary) => summary.fmt(f),
+        }
+    }
+}
+
+/// Load state of one GPU
+enum State {
+    /// Above the threshold, but not for long enough yet
+    Pending(LoadSummary),
+    /// Sustained load
+    Active(LoadSummary),
+}
+
+/// Detects sustained load per GPU from the sample stream
+///
+/// A GPU is loaded while its utilization is above `threshold`. Load that lasts
+/// at least `min_duration` is reported when it starts and summarized when it
+/// ends; shorter spikes are ignored.
+pub struct LoadDetector {
+    threshold: u32,
+    min_duration: Duration,
+    states: HashMap<u32, State>,
+}
+
+impl LoadDetector {
+    /// Create a detector
+    pub fn new(threshold: u32, min_duration: Duration) -> Self {
+        Self {
+            threshold,
+            min_duration,
+            states: HashMap::new(),
+        }
+    }
+
+    /// Feed a sample, returning the load changes it caused
+    pub fn update(&mut self, gpus: &[GpuInfo]) -> Vec<LoadEvent> {
+        gpus.iter().filter_map(|gpu| self.observe(gpu.into())).collect()
+    }
+
+    /// Advance the state of one GPU
+    fn observe(&mut self, sample: Sample) -> Option<LoadEvent> {
+        let loaded = sample.utilization > self.threshold;
+        let (next, event) = match (self.states.remove(&sample.gpu), loaded) {
+            (None, true) => (Some(State::Pending(LoadSummary::new(sample))), None),
+            (None, false) => (None, None),
+            (Some(State::Pending(mut summary)), true) => {
+                summary.add(sample);
+                if summary.duration() >= self.min_duration {
+                    let started = LoadEvent::Started { gpu: sample.gpu, since: summary.started_at };
+                    (Some(State::Active(summary)), Some(started))
+                } else {
+                    (Some(State::Pending(summary)), None)
+                }
+            }
+            // A spike shorter than the minimum duration
+            (Some(State::Pending(_)), false) => (None, None),
+            (Some(State::Active(mut summary)), true) => {
+                summary.add(sample);
+                (Some(State::Active(summary)), None)
+            }
+            (Some(State::Active(summary)), false) => (None, Some(LoadEvent::Ended(summary))),
+        };
+        if let Some(next) = next {
+            self.states.insert(sample.gpu, next);
+        }
+        event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed one sample per second given as (utilization, watts)
+    fn run(detector: &mut LoadDetector, samples: &[(u32, f32)]) -> Vec<LoadEvent> {
+        samples
+            .iter()
+            .enumerate()
+            .filter_map(|(second, &(utilization, power))| {
+                detector.observe(Sample {
+                    gpu: 0,
+                    at: second as u64 * 1000,
+                    utilization,
+                    temperature: 70,
+                    power,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_short_spike_ignored() {
+        let mut detector = LoadDetector::new(50, Duration::from_secs(5));
+        assert!(run(&mut detector, &[(90, 200.0), (90, 200.0), (10, 50.0)]).is_empty());
+    }
+
+    #[test]
+    fn test_sustained_load() {
+        let mut detector = LoadDetector::new(50, Duration::from_secs(5));
+        let mut samples = vec![(10, 50.0)];
+        samples.extend([(90, 200.0); 7]);
+        samples.push((10, 50.0));
+        let events = run(&mut detector, &samples);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], LoadEvent::Started { gpu: 0, since: 1000 });
+        let LoadEvent::Ended(summary) = events[1] else {
+            panic!("expected load to end");
+        };
+        assert_eq!(summary.duration(), Duration::from_secs(6));
+        assert_eq!(summary.temperature_avg(), 70.0);
+        assert_eq!(summary.power_peak, 200.0);
+        assert_eq!(summary.energy_joules, 1200.0);
+    }
+}
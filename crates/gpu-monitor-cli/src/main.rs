@@ -1,214 +1,1172 @@
-//! GPU Monitor CLI
-//!
-//! Terminal-based GPU monitoring tool with multiple output modes.
-
-mod app;
-mod tui;
-mod ui;
-
-use clap::{Parser, Subcommand};
-use gpu_monitor_core::GpuMonitor;
-
-/// GPU Monitor - Real-time NVIDIA GPU monitoring
-#[derive(Parser)]
-#[command(name = "gpu-monitor")]
-#[command(author, version, about, long_about = None)]
-struct Cli {
-    /// Print GPU info once and exit (similar to nvidia-smi)
-    #[arg(short, long)]
-    once: bool,
-
-    /// Continuous output mode (TUI with charts)
-    #[arg(short, long)]
-    watch: bool,
-
-    /// Output as JSON
-    #[arg(short, long)]
-    json: bool,
-
-    /// Refresh interval in milliseconds (default: 1000)
-    #[arg(short, long, default_value = "1000")]
-    interval: u64,
-
-    #[command(subcommand)]
-    command: Option<Commands>,
-}
-
-#[derive(Subcommand)]
-enum Commands {
-    /// Show GPU processes only
-    Processes,
-}
-
-fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
-
-    // Initialize tracing for debug logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::WARN.into()),
-        )
-        .init();
-
-    // Initialize monitor
-    let monitor = match GpuMonitor::new() {
-        Ok(m) => m,
-        Err(e) => {
-            eprintln!("Error: Failed to initialize GPU monitor");
-            eprintln!("Make sure NVIDIA drivers are installed and you have an NVIDIA GPU.");
-            eprintln!("Details: {}", e);
-            std::process::exit(1);
-        }
-    };
-
-    // Handle subcommands
-    if let Some(cmd) = &cli.command {
-        match cmd {
-            Commands::Processes => {
-                return print_processes(&monitor, cli.json);
-            }
-        }
-    }
-
-    // Handle output modes
-    if cli.once {
-        print_gpu_info(&monitor, cli.json)?;
-    } else if cli.json {
-        // Continuous JSON stream if watch is set, otherwise once
-        if cli.watch {
-            run_json_watch(&monitor, cli.interval)?;
-        } else {
-            print_gpu_info(&monitor, true)?;
-        }
-    } else {
-        // Default or --watch: launch TUI
-        run_tui(&monitor, cli.interval)?;
-    }
-
-    Ok(())
-}
-
-/// Print GPU info once
-fn print_gpu_info(monitor: &GpuMonitor, json: bool) -> anyhow::Result<()> {
-    let gpus = monitor.get_all_gpu_info()?;
-
-    if json {
-        println!("{}", serde_json::to_string_pretty(&gpus)?);
-    } else {
-        for gpu in &gpus {
-            println!("╭─────────────────────────────────────────────────────────────╮");
-            println!("│ GPU {}: {:<48} │", gpu.device.index, gpu.device.name);
-            println!("├─────────────────────────────────────────────────────────────┤");
-            println!(
-                "│ GPU Usage:    {:>3}%    Memory: {:>5.1}/{:.1} GiB ({:>3.0}%)        │",
-                gpu.metrics.gpu_utilization,
-                gpu.memory.used_gib(),
-                gpu.memory.total_gib(),
-                gpu.memory.usage_percent()
-            );
-            println!(
-                "│ Temperature:  {:>3}°C   Power:  {:>5.1}/{} W                    │",
-                gpu.metrics.temperature,
-                gpu.metrics.power_watts(),
-                gpu.device.power_limit
-            );
-            if let Some(fan) = gpu.metrics.fan_speed {
-                println!("│ Fan Speed:    {:>3}%                                          │", fan);
-            }
-            println!(
-                "│ Clocks:       Graphics {:>4} MHz  Memory {:>4} MHz          │",
-                gpu.metrics.clock_graphics, gpu.metrics.clock_memory
-            );
-
-            if !gpu.processes.is_empty() {
-                println!("├─────────────────────────────────────────────────────────────┤");
-                println!("│ Processes:                                                  │");
-                for proc in &gpu.processes {
-                    println!(
-                        "│   {:>6}  {:<30} {:>6} MiB  {:>5} │",
-                        proc.pid,
-                        truncate_str(&proc.name, 30),
-                        proc.gpu_memory_mib(),
-                        proc.process_type.short_label()
-                    );
-                }
-            }
-            println!("╰─────────────────────────────────────────────────────────────╯");
-        }
-    }
-
-    Ok(())
-}
-
-/// Print GPU processes only
-fn print_processes(monitor: &GpuMonitor, json: bool) -> anyhow::Result<()> {
-    let gpus = monitor.get_all_gpu_info()?;
-
-    if json {
-        let all_processes: Vec<_> = gpus
-            .iter()
-            .flat_map(|g| {
-                g.processes.iter().map(|p| {
-                    serde_json::json!({
-                        "gpu_index": g.device.index,
-                        "pid": p.pid,
-                        "name": p.name,
-                        "gpu_memory_mib": p.gpu_memory_mib(),
-                        "type": p.process_type
-                    })
-                })
-            })
-            .collect();
-        println!("{}", serde_json::to_string_pretty(&all_processes)?);
-    } else {
-        println!("╭─────────────────────────────────────────────────────────────╮");
-        println!("│ GPU Processes                                               │");
-        println!("├───────┬────────┬────────────────────────────┬────────┬──────┤");
-        println!("│  GPU  │   PID  │ Name                       │ Memory │ Type │");
-        println!("├───────┼────────┼────────────────────────────┼────────┼──────┤");
-
-        for gpu in &gpus {
-            for proc in &gpu.processes {
-                println!(
-                    "│  {:>3}  │ {:>6} │ {:<26} │ {:>4} MB│ {:>4} │",
-                    gpu.device.index,
-                    proc.pid,
-                    truncate_str(&proc.name, 26),
-                    proc.gpu_memory_mib(),
-                    proc.process_type.short_label()
-                );
-            }
-        }
-        println!("╰───────┴────────┴────────────────────────────┴────────┴──────╯");
-    }
-
-    Ok(())
-}
-
-/// Run continuous JSON output
-fn run_json_watch(monitor: &GpuMonitor, interval: u64) -> anyhow::Result<()> {
-    use std::time::Duration;
-    loop {
-        let gpus = monitor.get_all_gpu_info()?;
-        println!("{}", serde_json::to_string(&gpus)?);
-        std::thread::sleep(Duration::from_millis(interval));
-    }
-}
-
-/// Run interactive TUI
-fn run_tui(monitor: &GpuMonitor, interval: u64) -> anyhow::Result<()> {
-    let mut terminal = tui::init()?;
-    let result = app::App::new(interval).run(&mut terminal, monitor);
-    tui::restore()?;
-    result
-}
-
-/// Truncate string to max length
-fn truncate_str(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len - 3])
-    }
-}
+//! GPU Monitor CLI
+//!
+//! Terminal-based GPU monitoring tool with multiple output modes.
+
+mod alert;
+mod app;
+mod config;
+mod diff;
+mod export;
+mod format;
+mod history;
+mod load;
+mod select;
+#[cfg(unix)]
+mod socket;
+mod tui;
+mod ui;
+
+use app::{ChartMetric, ProcColumn, View};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use format::{MemoryUnit, NumberFormat};
+use gpu_monitor_core::{AggregateStats, ClockState, GpuInfo, GpuMonitor};
+use std::io::{IsTerminal, Write};
+
+/// Tracing target for per-sample events (enabled by `--trace-samples`)
+const SAMPLE_TARGET: &str = "gpu_monitor::sample";
+
+/// Minimum refresh interval in milliseconds
+const MIN_INTERVAL_MS: u64 = 50;
+
+/// `check` exit code: NVML works but no GPU devices were found
+const EXIT_NO_DEVICES: i32 = 2;
+
+/// `check` exit code: NVML could not be initialized
+const EXIT_NVML_UNAVAILABLE: i32 = 3;
+
+/// GPU Monitor - Real-time NVIDIA GPU monitoring
+#[derive(Parser)]
+#[command(name = "gpu-monitor")]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Print GPU info once and exit (similar to nvidia-smi)
+    #[arg(short, long)]
+    once: bool,
+
+    /// Continuous output mode (TUI with charts)
+    #[arg(short, long)]
+    watch: bool,
+
+    /// Output as JSON
+    #[arg(short, long, global = true)]
+    json: bool,
+
+    /// Only show these GPUs: indices, UUID prefixes or name patterns (e.g. 0,GPU-1a2b,rtx4090,*a100*)
+    #[arg(long, value_delimiter = ',', env = "GPU_MONITOR_GPU")]
+    gpu: Vec<String>,
+
+    /// Unit for memory sizes in plain output
+    #[arg(long, value_enum, default_value = "gib", env = "GPU_MONITOR_UNITS")]
+    units: MemoryUnit,
+
+    /// Only include these fields in JSON output (e.g. index,gpu_utilization,temperature)
+    #[arg(long, value_delimiter = ',')]
+    fields: Vec<String>,
+
+    /// Decimal places for memory GiB and power watts in JSON output
+    #[arg(long, global = true, default_value = "2", env = "GPU_MONITOR_PRECISION")]
+    precision: u32,
+
+    /// Cut process names and command lines in JSON output to this many characters
+    #[arg(long, global = true, env = "GPU_MONITOR_MAX_NAME_LEN")]
+    max_name_len: Option<usize>,
+
+    /// Emit numeric JSON fields as {"value": 61, "unit": "C"} so the output describes itself
+    #[arg(long, global = true)]
+    json_with_units: bool,
+
+    /// Refresh interval in milliseconds (default: 1000, minimum: 50)
+    #[arg(short, long, default_value = "1000", env = "GPU_MONITOR_INTERVAL")]
+    interval: u64,
+
+    /// Number of samples kept for TUI charts
+    #[arg(long, default_value = "60", env = "GPU_MONITOR_HISTORY")]
+    history: usize,
+
+    /// Show metrics as history charts or current-value gauges in the TUI (toggle with g)
+    #[arg(long, value_enum, default_value = "charts", env = "GPU_MONITOR_VIEW")]
+    view: View,
+
+    /// Metrics shown in the two TUI charts (util, mem, temp, power)
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "util,mem", env = "GPU_MONITOR_CHART")]
+    chart: Vec<ChartMetric>,
+
+    /// Columns of the TUI process table (pid, name, user, mem, mem_pct, type, sm_util, cmdline, runtime)
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "pid,name,mem,mem_pct,type,runtime", env = "GPU_MONITOR_PROC_COLUMNS")]
+    proc_columns: Vec<ProcColumn>,
+
+    /// Keep the recent samples in this NDJSON file so they survive a crash
+    #[arg(long, value_name = "PATH")]
+    persist_history: Option<std::path::PathBuf>,
+
+    /// How much history to keep in the --persist-history file (e.g. 30s, 5m, 1h)
+    #[arg(long, value_parser = history::parse_duration, default_value = "5m")]
+    persist_window: std::time::Duration,
+
+    /// Smooth TUI charts by interpolating between samples (for slow intervals)
+    #[arg(long)]
+    smooth: bool,
+
+    /// Alert when a metric reaches a threshold in watch mode (e.g. temp=85,power=300,util=95,mem=90)
+    #[arg(long, value_parser = alert::parse_rule, value_delimiter = ',', env = "GPU_MONITOR_ALERT")]
+    alert: Vec<alert::AlertRule>,
+
+    /// Command run on each alert; {gpu}, {metric} and {value} are substituted
+    #[arg(long, value_name = "COMMAND", requires = "alert")]
+    on_alert: Option<String>,
+
+    /// Minimum time between repeated alerts for a breach that persists
+    #[arg(long, value_parser = history::parse_duration, default_value = "1m", env = "GPU_MONITOR_ALERT_COOLDOWN")]
+    alert_cooldown: std::time::Duration,
+
+    /// Report sustained load and print a summary to stderr when it ends (JSON watch mode)
+    #[arg(long, requires = "json")]
+    detect_load: bool,
+
+    /// GPU utilization percentage above which a GPU counts as loaded
+    #[arg(long, default_value = "50", env = "GPU_MONITOR_LOAD_THRESHOLD")]
+    load_threshold: u32,
+
+    /// How long load must last before it is reported (e.g. 5s, 1m)
+    #[arg(long, value_parser = history::parse_duration, default_value = "5s")]
+    load_min_duration: std::time::Duration,
+
+    /// Allow destructive operations such as `reset`
+    #[arg(long)]
+    dangerous: bool,
+
+    /// Deterministic output for diffable snapshots: GPUs by index, processes by PID
+    #[arg(long)]
+    stable: bool,
+
+    /// Print fleet totals (GPU count, average utilization, memory, power) once and exit
+    #[arg(long, conflicts_with_all = ["watch", "diff"])]
+    summary: bool,
+
+    /// Print only what changed between samples (metrics, throttling, processes) instead of the full state
+    #[arg(long, conflicts_with_all = ["json", "once"])]
+    diff: bool,
+
+    /// Emit every sample as a structured tracing event on stderr (redirect it when using the TUI)
+    #[arg(long)]
+    trace_samples: bool,
+
+    /// Serve the JSON stream to every client connecting to this Unix socket instead of stdout
+    #[arg(long, value_name = "PATH")]
+    socket: Option<std::path::PathBuf>,
+
+    /// Show a one-line status on this stream in JSON watch mode; JSON goes to the other one
+    #[arg(long, value_enum)]
+    status: Option<OutputStream>,
+
+    /// Config file with defaults for these flags (default: ~/.config/gpu-monitor/config.toml)
+    #[arg(long, value_name = "PATH", env = "GPU_MONITOR_CONFIG")]
+    config: Option<std::path::PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+impl Cli {
+    /// JSON serialization settings from the flags
+    fn json_options(&self) -> export::JsonOptions {
+        export::JsonOptions {
+            precision: self.precision,
+            max_name_len: self.max_name_len,
+            with_units: self.json_with_units,
+        }
+    }
+
+    /// Check if this run draws the TUI, which owns the terminal
+    fn runs_tui(&self) -> bool {
+        match &self.command {
+            Some(Commands::Processes { watch }) => *watch,
+            Some(_) => false,
+            None => !(self.summary || self.once || self.diff || self.json || self.socket.is_some()),
+        }
+    }
+}
+
+/// Standard output stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputStream {
+    /// Standard output
+    Stdout,
+    /// Standard error
+    Stderr,
+}
+
+impl OutputStream {
+    /// Get a writer for this stream
+    fn writer(self) -> Box<dyn Write> {
+        match self {
+            Self::Stdout => Box::new(std::io::stdout()),
+            Self::Stderr => Box::new(std::io::stderr()),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Show a single GPU
+    Gpu {
+        /// GPU index
+        index: u32,
+    },
+    /// Show GPU processes only
+    Processes {
+        /// Interactive table of all GPUs' processes; `o` cycles the sort like top
+        #[arg(short, long)]
+        watch: bool,
+    },
+    /// Show recent GPU jobs, including exited ones, from the driver's accounting buffer
+    ///
+    /// Requires accounting mode (`nvidia-smi -am 1`, as root).
+    Accounting {
+        /// GPU index (default: all selected GPUs)
+        index: Option<u32>,
+    },
+    /// Show GPU interconnect topology matrix
+    #[command(alias = "topo")]
+    Topology {
+        /// Instead, show how NVML indices, CUDA ordinals, minor numbers and PCI bus IDs line up
+        #[arg(long)]
+        ordering: bool,
+    },
+    /// Show the CPU cores closest to each GPU (for taskset / thread pinning)
+    Affinity,
+    /// Reset a hung GPU (requires root and --dangerous)
+    Reset {
+        /// GPU index to reset
+        index: u32,
+        /// Reset even if processes are running or a display is attached
+        #[arg(long)]
+        force: bool,
+    },
+    /// Export a single snapshot of all GPUs
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value = "json")]
+        format: export::ExportFormat,
+        /// File to write, or `-` for stdout
+        #[arg(long, default_value = "-")]
+        output: String,
+    },
+    /// Check for a usable GPU (exit 0 if present, 2 if no devices, 3 if NVML is unavailable)
+    Check {
+        /// Print the result
+        #[arg(short, long)]
+        verbose: bool,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    // Flags and environment variables take precedence over the config file
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    config::Config::load(cli.config.as_deref())?.apply(&mut cli, &matches)?;
+
+    // Initialize tracing for debug logging (on stderr to keep stdout parseable);
+    // the TUI owns the terminal, so there it is dropped unless stderr is redirected
+    let quiet = cli.runs_tui() && std::io::stderr().is_terminal();
+    if quiet && cli.trace_samples {
+        anyhow::bail!("--trace-samples would draw over the TUI; redirect stderr (e.g. 2> samples.log)");
+    }
+    let mut filter =
+        tracing_subscriber::EnvFilter::from_default_env().add_directive(tracing::Level::WARN.into());
+    if cli.trace_samples {
+        filter = filter.add_directive(format!("{}=info", SAMPLE_TARGET).parse()?);
+    }
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(move || -> Box<dyn Write> {
+            if quiet {
+                Box::new(std::io::sink())
+            } else {
+                Box::new(std::io::stderr())
+            }
+        })
+        .init();
+
+    // Guard against intervals that would spin the CPU
+    let interval = clamp_interval(cli.interval);
+    if interval != cli.interval {
+        eprintln!(
+            "Warning: interval {} ms is too small, using {} ms",
+            cli.interval, interval
+        );
+        cli.interval = interval;
+    }
+
+    // The check subcommand reports NVML failures through its exit code
+    if let Some(Commands::Check { verbose }) = cli.command {
+        std::process::exit(run_check(verbose));
+    }
+
+    // Initialize monitor
+    let monitor = match GpuMonitor::new() {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("Error: Failed to initialize GPU monitor");
+            eprintln!("Make sure NVIDIA drivers are installed and you have an NVIDIA GPU.");
+            eprintln!("Details: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Resolve --gpu once, so the watch loops filter by concrete indices
+    let selected = if cli.gpu.is_empty() {
+        Vec::new()
+    } else {
+        let devices: Vec<_> = monitor.get_all_gpu_info()?.into_iter().map(|g| g.device).collect();
+        select::resolve(&cli.gpu, &devices)?
+    };
+
+    // Handle subcommands
+    if let Some(cmd) = &cli.command {
+        match cmd {
+            Commands::Gpu { index } => {
+                return print_single_gpu(&monitor, *index, cli.json, &cli.fields, cli.json_options(), cli.units);
+            }
+            Commands::Processes { watch: true } => {
+                let mut app = tui_app(&cli, selected)?;
+                app.process_view = true;
+                return run_tui(&monitor, app);
+            }
+            Commands::Processes { watch: false } => {
+                return print_processes(&monitor, cli.json, cli.stable, cli.max_name_len);
+            }
+            Commands::Accounting { index } => {
+                let indices = match index {
+                    Some(index) => vec![*index],
+                    None if selected.is_empty() => (0..monitor.device_count()?).collect(),
+                    None => selected,
+                };
+                return print_accounting(&monitor, &indices, cli.json);
+            }
+            Commands::Topology { ordering: true } => {
+                return print_ordering(&monitor, cli.json);
+            }
+            Commands::Topology { ordering: false } => {
+                return print_topology(&monitor, cli.json);
+            }
+            Commands::Affinity => {
+                return print_affinity(&monitor, cli.json);
+            }
+            Commands::Export { format, output } => {
+                let mut gpus = monitor.get_all_gpu_info()?;
+                select::retain(&mut gpus, &selected);
+                let rendered = export::export(&gpus, *format, &cli.fields, cli.json_options())?;
+                if output == "-" {
+                    print!("{}", rendered);
+                } else {
+                    std::fs::write(output, rendered)?;
+                }
+                return Ok(());
+            }
+            Commands::Reset { index, force } => {
+                if !cli.dangerous {
+                    anyhow::bail!("resetting a GPU kills its workloads; pass --dangerous to confirm");
+                }
+                monitor.reset_gpu(*index, *force)?;
+                println!("GPU {} reset", index);
+                return Ok(());
+            }
+            Commands::Check { .. } => unreachable!("handled before monitor initialization"),
+        }
+    }
+
+    let alerts = (!cli.alert.is_empty())
+        .then(|| alert::Alerts::new(cli.alert.clone(), cli.on_alert.clone(), cli.alert_cooldown));
+
+    // Handle output modes
+    if cli.summary {
+        print_summary(&monitor, &selected, cli.json, cli.json_options())?;
+    } else if cli.once {
+        print_gpu_info(&monitor, &selected, cli.json, &cli.fields, cli.json_options(), cli.units, cli.stable)?;
+    } else if cli.diff {
+        run_diff_watch(&monitor, cli.interval, &selected)?;
+    } else if cli.json || cli.socket.is_some() {
+        // Continuous JSON stream if watch is set or serving a socket, otherwise once
+        if cli.watch || cli.socket.is_some() {
+            run_json_watch(&monitor, &cli, &selected, open_history(&cli)?, alerts)?;
+        } else {
+            print_gpu_info(&monitor, &selected, true, &cli.fields, cli.json_options(), cli.units, cli.stable)?;
+        }
+    } else {
+        // Default or --watch: launch TUI
+        let mut app = tui_app(&cli, selected)?;
+        app.persist = open_history(&cli)?;
+        app.alerts = alerts;
+        run_tui(&monitor, app)?;
+    }
+
+    Ok(())
+}
+
+/// Open the --persist-history file, only for the watch modes that record samples
+fn open_history(cli: &Cli) -> anyhow::Result<Option<history::HistoryFile>> {
+    cli.persist_history
+        .clone()
+        .map(|path| history::HistoryFile::open(path, cli.persist_window))
+        .transpose()
+}
+
+/// Check whether a usable GPU is present and return the exit code
+fn run_check(verbose: bool) -> i32 {
+    let monitor = match GpuMonitor::new() {
+        Ok(m) => m,
+        Err(e) => {
+            if verbose {
+                println!("NVML unavailable: {}", e);
+            }
+            return EXIT_NVML_UNAVAILABLE;
+        }
+    };
+
+    match monitor.device_count() {
+        Ok(count) if count > 0 => {
+            if verbose {
+                println!("{} NVIDIA GPU(s) available", count);
+            }
+            0
+        }
+        Ok(_) => {
+            if verbose {
+                println!("NVML initialized but no NVIDIA GPUs found");
+            }
+            EXIT_NO_DEVICES
+        }
+        Err(e) => {
+            if verbose {
+                println!("Failed to query GPU devices: {}", e);
+            }
+            EXIT_NO_DEVICES
+        }
+    }
+}
+
+/// Print GPU info once
+fn print_gpu_info(
+    monitor: &GpuMonitor,
+    selected: &[u32],
+    json: bool,
+    fields: &[String],
+    options: export::JsonOptions,
+    units: MemoryUnit,
+    stable: bool,
+) -> anyhow::Result<()> {
+    let mut gpus = monitor.get_all_gpu_info()?;
+    select::retain(&mut gpus, selected);
+    if stable {
+        stabilize(&mut gpus);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&export::gpus_to_json(&gpus, fields, options)?)?);
+    } else {
+        print_gpu_boxes(&gpus, units);
+    }
+
+    Ok(())
+}
+
+/// Print a single GPU selected by index
+fn print_single_gpu(
+    monitor: &GpuMonitor,
+    index: u32,
+    json: bool,
+    fields: &[String],
+    options: export::JsonOptions,
+    units: MemoryUnit,
+) -> anyhow::Result<()> {
+    let gpu = monitor.get_gpu_info(index)?;
+
+    if json {
+        let value = export::gpu_to_json(&gpu, options)?;
+        let value = if fields.is_empty() { value } else { export::project_fields(&value, fields) };
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        print_gpu_boxes(std::slice::from_ref(&gpu), units);
+    }
+
+    Ok(())
+}
+
+/// Print one box per GPU in the plain output format
+fn print_gpu_boxes(gpus: &[GpuInfo], units: MemoryUnit) {
+    let fmt = NumberFormat::from_env();
+
+    for gpu in gpus {
+        println!("╭─────────────────────────────────────────────────────────────╮");
+        println!("│ GPU {}: {:<48} │", gpu.device.index, gpu.device.name);
+        println!("├─────────────────────────────────────────────────────────────┤");
+        if let Some(minor) = gpu.device.minor_number {
+            println!("│ Device Node:  {:<45} │", format!("/dev/nvidia{}", minor));
+        }
+        if let Some(node) = gpu.device.numa_node {
+            println!("│ NUMA Node:    {:<45} │", node);
+        }
+        if let Some(model) = gpu.device.driver_model {
+            println!("│ Driver Model: {:<45} │", model.label());
+        }
+        if let Some(vbios) = &gpu.device.vbios_version {
+            println!("│ VBIOS:        {:<45} │", vbios);
+        }
+        println!(
+            "│ GPU Usage:    {:>3}%    Memory: {:<30} │",
+            gpu.metrics.gpu_utilization,
+            format!(
+                "{}/{} {} ({:.0}%)",
+                fmt.memory(gpu.memory.used, units),
+                fmt.memory(gpu.memory.total, units),
+                units.label(),
+                gpu.memory.usage_percent()
+            )
+        );
+        println!(
+            "│ Mem Bandwidth:{:>3}%    (memory controller load)              │",
+            gpu.metrics.memory_controller_load()
+        );
+        println!(
+            "│ Temperature:  {:>3}°C   Power:  {:>5.1}/{} W                    │",
+            gpu.metrics.temperature,
+            gpu.metrics.power_watts(),
+            gpu.device.power_limit
+        );
+        if gpu.metrics.temperatures.len() > 1 {
+            let sensors: Vec<_> =
+                gpu.metrics.temperatures.iter().map(|(sensor, temp)| format!("{} {}°C", sensor, temp)).collect();
+            println!("│ Sensors:      {:<45} │", truncate_str(&sensors.join("  "), 45));
+        }
+        if let (Some(margin), Some(slowdown)) = (gpu.thermal_margin(), gpu.device.temp_slowdown) {
+            println!(
+                "│ Thermal:      {:<45} │",
+                format!("{}°C from slowdown ({}°C)", margin, slowdown)
+            );
+        }
+        if !gpu.device.has_fan {
+            println!("│ Fan Speed:    {:<45} │", "passive (no fans)");
+        } else if gpu.metrics.fan_speeds.len() > 1 {
+            let fans: Vec<_> = gpu.metrics.fan_speeds.iter().map(|fan| format!("{:>3}%", fan)).collect();
+            println!("│ Fan Speed:    {:<45} │", fans.join(" "));
+        } else if let Some(fan) = gpu.metrics.fan_speed {
+            println!("│ Fan Speed:    {:>3}%                                          │", fan);
+        }
+        println!(
+            "│ Clocks:       Graphics {:>4} MHz  Memory {:>4} MHz          │",
+            gpu.metrics.clock_graphics, gpu.metrics.clock_memory
+        );
+        let range = gpu.device.clock_range;
+        println!(
+            "│ Cur/Base/Max: {:<45} │",
+            format!(
+                "Gfx {}  Mem {}",
+                format_clock_levels(
+                    gpu.metrics.clock_graphics,
+                    gpu.device.clock_graphics_base,
+                    range.map(|r| r.graphics_max)
+                ),
+                format_clock_levels(
+                    gpu.metrics.clock_memory,
+                    gpu.device.clock_memory_base,
+                    range.map(|r| r.memory_max)
+                )
+            )
+        );
+        if let Some(boost) = gpu.device.boost_clock_rated {
+            println!("│ Rated Boost:  {:<45} │", format!("Gfx {} MHz", boost));
+        }
+        println!(
+            "│ Video:        {:<45} │",
+            format!(
+                "Encoder {}  Decoder {}{}",
+                format_engine(gpu.encoder_utilization()),
+                format_engine(gpu.decoder_utilization()),
+                // Both engines are normally sampled over the same window
+                gpu.metrics
+                    .encoder_sampling_us
+                    .or(gpu.metrics.decoder_sampling_us)
+                    .map(|us| format!("  (over {} ms)", us / 1000))
+                    .unwrap_or_default()
+            )
+        );
+        if gpu.metrics.jpeg_utilization.is_some() || gpu.metrics.ofa_utilization.is_some() {
+            println!(
+                "│ Media:        {:<45} │",
+                format!(
+                    "JPEG {}  OFA {}",
+                    format_engine(gpu.metrics.jpeg_utilization),
+                    format_engine(gpu.metrics.ofa_utilization)
+                )
+            );
+        }
+        if gpu.device.clock_state == ClockState::ApplicationLocked {
+            let locked = gpu.device.app_clock_graphics.unwrap_or_default();
+            println!("│ App Clocks:   {:<45} │", format!("locked @ {} MHz", locked));
+        }
+        if let Some(enabled) = gpu.device.persistence_mode {
+            println!("│ Persistence:  {:<45} │", if enabled { "enabled" } else { "disabled" });
+        }
+        if gpu.driving_display {
+            println!("│ Display:      {:<45} │", "active (desktop adds baseline load)");
+        }
+        if let Some(current) = gpu.device.ecc_enabled_current {
+            let state = |enabled| if enabled { "enabled" } else { "disabled" };
+            let ecc = if gpu.device.ecc_change_pending() {
+                format!("{} ({} after reboot)", state(current), state(!current))
+            } else {
+                state(current).to_string()
+            };
+            println!("│ ECC:          {:<45} │", ecc);
+        }
+        if let Some(reserved) = gpu.memory.ecc_reserved.filter(|r| *r > 0) {
+            println!(
+                "│ ECC Reserve:  {:<45} │",
+                format!(
+                    "{} {} of {} physical",
+                    fmt.memory(reserved, units),
+                    units.label(),
+                    fmt.memory(gpu.memory.physical_total(), units)
+                )
+            );
+        }
+        for instance in &gpu.mig_instances {
+            let mig = format!(
+                "GI {} CI {}  {:<8} {} {}",
+                instance.gpu_instance_id,
+                instance.compute_instance_id,
+                instance.profile,
+                fmt.memory(instance.memory_total, units),
+                units.label()
+            );
+            println!("│ MIG:          {:<45} │", truncate_str(&mig, 45));
+        }
+        if let Some(replays) = gpu.metrics.pcie_replay_count.filter(|r| *r > 0) {
+            println!("│ PCIe Replays: {:<45} │", replays);
+        }
+        if gpu.memory_retirement_pending() {
+            println!("│ Mem Health:   {:<45} │", "PENDING RETIREMENT (reset GPU to apply)");
+        }
+        if let Some(diagnostic) = gpu.throttle_diagnostic() {
+            println!("│ Throttle:     {:<45} │", truncate_str(&diagnostic, 45));
+        }
+        if gpu.power_brake_active() {
+            println!("│ Power Brake:  {:<45} │", "EXTERNAL (PSU/VRM limit)");
+        }
+        for warning in &gpu.warnings {
+            println!("│ Warning:      {:<45} │", truncate_str(warning, 45));
+        }
+
+        if !gpu.processes.is_empty() {
+            println!("├─────────────────────────────────────────────────────────────┤");
+            println!("│ Processes:                                                  │");
+            for proc in &gpu.processes {
+                println!(
+                    "│   {:>6}  {:<30} {:>6} {:<3}  {:>5} │",
+                    proc.pid,
+                    truncate_str(&proc.name, 30),
+                    fmt.memory(proc.gpu_memory, units),
+                    units.label(),
+                    proc.process_type.short_label()
+                );
+            }
+            if let Some(overhead) = gpu.memory_overhead().filter(|o| *o > 0) {
+                println!(
+                    "│   {:>6}  {:<30} {:>6} {:<3}  {:>5} │",
+                    "-",
+                    "other/reserved",
+                    fmt.memory(overhead, units),
+                    units.label(),
+                    ""
+                );
+            }
+        }
+        println!("╰─────────────────────────────────────────────────────────────╯");
+    }
+}
+
+/// Print totals over the selected GPUs
+fn print_summary(monitor: &GpuMonitor, selected: &[u32], json: bool, options: export::JsonOptions) -> anyhow::Result<()> {
+    let mut gpus = monitor.get_all_gpu_info()?;
+    select::retain(&mut gpus, selected);
+    let stats = AggregateStats::from_gpus(&gpus);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&export::summary_to_json(&stats, options)?)?);
+        return Ok(());
+    }
+
+    let gib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    println!(
+        "{} GPUs │ util avg {:.1}% │ memory {:.1}/{:.1} GiB │ power {:.1} W │ max temp {}°C │ {} processes",
+        stats.gpu_count,
+        stats.avg_utilization,
+        gib(stats.total_memory_used),
+        gib(stats.total_memory),
+        stats.total_power,
+        stats.max_temperature,
+        stats.process_count
+    );
+    Ok(())
+}
+
+/// Print GPU processes only
+fn print_processes(monitor: &GpuMonitor, json: bool, stable: bool, max_name_len: Option<usize>) -> anyhow::Result<()> {
+    let mut gpus = monitor.get_all_gpu_info()?;
+    if stable {
+        stabilize(&mut gpus);
+    }
+    let fmt = NumberFormat::from_env();
+
+    if json {
+        let all_processes: Vec<_> = gpus
+            .iter()
+            .flat_map(|g| {
+                g.processes.iter().map(|p| {
+                    serde_json::json!({
+                        "gpu_index": g.device.index,
+                        "pid": p.pid,
+                        "name": match max_name_len {
+                            Some(max) => export::truncate_name(&p.name, max),
+                            None => p.name.clone(),
+                        },
+                        "gpu_memory_mib": p.gpu_memory_mib(),
+                        "type": p.process_type,
+                        "active": p.active,
+                        "runtime_seconds": p.runtime_seconds
+                    })
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&all_processes)?);
+    } else {
+        println!("╭─────────────────────────────────────────────────────────────╮");
+        println!("│ GPU Processes                                               │");
+        println!("├───────┬────────┬────────────────────────────┬────────┬──────┤");
+        println!("│  GPU  │   PID  │ Name                       │ Memory │ Type │");
+        println!("├───────┼────────┼────────────────────────────┼────────┼──────┤");
+
+        for gpu in &gpus {
+            for proc in &gpu.processes {
+                println!(
+                    "│  {:>3}  │ {:>6} │ {:<26} │{:>6}MB│ {:>4} │",
+                    gpu.device.index,
+                    proc.pid,
+                    truncate_str(&proc.name, 26),
+                    fmt.integer(proc.gpu_memory_mib()),
+                    proc.process_type.short_label()
+                );
+            }
+        }
+        println!("╰───────┴────────┴────────────────────────────┴────────┴──────╯");
+    }
+
+    Ok(())
+}
+
+/// Print the accounting buffer of each GPU, most recent job first
+fn print_accounting(monitor: &GpuMonitor, indices: &[u32], json: bool) -> anyhow::Result<()> {
+    let mut rows = Vec::new();
+    for &index in indices {
+        match monitor.get_accounting_pids(index) {
+            Ok(records) => rows.extend(records.into_iter().map(|r| (index, r))),
+            // Skip GPUs without accounting unless nothing else can be shown
+            Err(e @ gpu_monitor_core::Error::AccountingDisabled(_)) if indices.len() > 1 => eprintln!("Warning: {}", e),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    if json {
+        let value: Vec<_> = rows
+            .iter()
+            .map(|(index, record)| {
+                let mut value = serde_json::to_value(record)?;
+                value["gpu_index"] = (*index).into();
+                Ok(value)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    let now_us = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0);
+    let percent = |value: Option<u32>| value.map(|v| format!("{}%", v)).unwrap_or_else(|| "-".to_string());
+    let fmt = NumberFormat::from_env();
+
+    println!("╭──────────────────────────────────────────────────────────────────────────╮");
+    println!("│ GPU Accounting                                                           │");
+    println!("├───────┬────────┬─────────┬───────────┬─────────┬──────┬──────┬───────────┤");
+    println!("│  GPU  │   PID  │ State   │ Started   │ Runtime │ GPU  │ Mem  │ Peak Mem  │");
+    println!("├───────┼────────┼─────────┼───────────┼─────────┼──────┼──────┼───────────┤");
+    for (index, record) in &rows {
+        let started = format!("{} ago", format::duration(now_us.saturating_sub(record.start_time_us) / 1_000_000));
+        let runtime = if record.is_running {
+            "-".to_string()
+        } else {
+            format::duration(record.run_time_ms / 1000)
+        };
+        let peak = record
+            .max_memory_usage
+            .map(|bytes| format!("{}MB", fmt.integer(bytes / (1024 * 1024))))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "│  {:>3}  │ {:>6} │ {:<7} │ {:>9} │ {:>7} │ {:>4} │ {:>4} │ {:>9} │",
+            index,
+            record.pid,
+            if record.is_running { "running" } else { "exited" },
+            started,
+            runtime,
+            percent(record.gpu_utilization),
+            percent(record.memory_utilization),
+            peak
+        );
+    }
+    println!("╰───────┴────────┴─────────┴───────────┴─────────┴──────┴──────┴───────────╯");
+
+    Ok(())
+}
+
+/// Print GPU interconnect topology matrix
+fn print_topology(monitor: &GpuMonitor, json: bool) -> anyhow::Result<()> {
+    let topology = monitor.get_topology()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&topology)?);
+    } else {
+        print!("      ");
+        for index in &topology.devices {
+            print!(" {:>6}", format!("GPU{}", index));
+        }
+        println!();
+        for (a, index) in topology.devices.iter().enumerate() {
+            print!("{:<6}", format!("GPU{}", index));
+            for b in 0..topology.devices.len() {
+                let label = topology.link(a, b).map(|l| l.label()).unwrap_or_default();
+                print!(" {:>6}", label);
+            }
+            println!();
+        }
+        println!();
+        println!("Legend:");
+        println!("  X    = Self");
+        println!("  NV#  = Connection traversing a bonded set of # NVLinks");
+        println!("  INT  = Devices on the same board");
+        println!("  PIX  = Connection traversing at most a single PCIe switch");
+        println!("  PXB  = Connection traversing multiple PCIe switches");
+        println!("  PHB  = Connection traversing a PCIe host bridge");
+        println!("  NODE = Connection traversing host bridges within a NUMA node");
+        println!("  SYS  = Connection traversing the SMP interconnect between NUMA nodes");
+    }
+
+    Ok(())
+}
+
+/// Print each GPU's NVML index, CUDA ordinal, minor number and PCI bus ID side by side
+///
+/// CUDA ordinals follow `CUDA_VISIBLE_DEVICES` in this environment.
+fn print_ordering(monitor: &GpuMonitor, json: bool) -> anyhow::Result<()> {
+    let gpus = monitor.get_all_gpu_info_opts(gpu_monitor_core::Options { processes: false })?;
+    let mut cuda_ordinals = vec![None; gpus.len()];
+    for ordinal in 0..gpus.len() as u32 {
+        let Ok(index) = monitor.cuda_ordinal_to_index(ordinal) else {
+            break;
+        };
+        if let Some(slot) = cuda_ordinals.get_mut(index as usize) {
+            *slot = Some(ordinal);
+        }
+    }
+    let visible = std::env::var("CUDA_VISIBLE_DEVICES").ok();
+    let order = std::env::var("CUDA_DEVICE_ORDER").ok();
+
+    if json {
+        let devices: Vec<_> = gpus
+            .iter()
+            .zip(&cuda_ordinals)
+            .map(|(gpu, ordinal)| {
+                serde_json::json!({
+                    "nvml_index": gpu.device.index,
+                    "cuda_ordinal": ordinal,
+                    "minor_number": gpu.device.minor_number,
+                    "pci_bus_id": gpu.device.pci_bus_id,
+                    "uuid": gpu.device.uuid,
+                    "name": gpu.device.name,
+                })
+            })
+            .collect();
+        let value = serde_json::json!({
+            "cuda_visible_devices": visible,
+            "cuda_device_order": order,
+            "devices": devices,
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("CUDA_VISIBLE_DEVICES={}", visible.as_deref().unwrap_or("(unset)"));
+    println!("CUDA_DEVICE_ORDER={}", order.as_deref().unwrap_or("(unset)"));
+    println!();
+    println!("{:<5} {:<5} {:<5} {:<16} {:<40} Name", "NVML", "CUDA", "Minor", "PCI Bus ID", "UUID");
+    let or_dash = |value: Option<u32>| value.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+    for (gpu, ordinal) in gpus.iter().zip(&cuda_ordinals) {
+        println!(
+            "{:<5} {:<5} {:<5} {:<16} {:<40} {}",
+            gpu.device.index,
+            or_dash(*ordinal),
+            or_dash(gpu.device.minor_number),
+            gpu.device.pci_bus_id,
+            gpu.device.uuid,
+            gpu.device.name
+        );
+    }
+    if order.as_deref() != Some("PCI_BUS_ID") {
+        println!();
+        println!("CUDA orders the fastest GPU first unless CUDA_DEVICE_ORDER=PCI_BUS_ID, so the CUDA column");
+        println!("assumes PCI order; set CUDA_DEVICE_ORDER=PCI_BUS_ID to make it exact.");
+    }
+
+    Ok(())
+}
+
+/// Print the recommended CPU cores for each GPU
+fn print_affinity(monitor: &GpuMonitor, json: bool) -> anyhow::Result<()> {
+    let gpus = monitor.get_all_gpu_info()?;
+    let mut rows = Vec::with_capacity(gpus.len());
+    for gpu in &gpus {
+        let cpus = monitor.recommended_cpu_set(gpu.device.index)?;
+        let list = cpus.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(",");
+        rows.push((gpu, cpus, list));
+    }
+
+    if json {
+        let value: Vec<_> = rows
+            .iter()
+            .map(|(gpu, cpus, list)| {
+                serde_json::json!({
+                    "gpu_index": gpu.device.index,
+                    "numa_node": gpu.device.numa_node,
+                    "cpus": list,
+                    "cpu_ranges": cpus,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        println!("GPU   NUMA  CPUs");
+        for (gpu, _, list) in &rows {
+            let numa = gpu.device.numa_node.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string());
+            println!("{:<5} {:<5} {}", format!("GPU{}", gpu.device.index), numa, list);
+        }
+        println!();
+        println!("Pin a process near a GPU with e.g. `taskset -c <CPUs> <command>`");
+    }
+
+    Ok(())
+}
+
+/// Run continuous JSON output
+fn run_json_watch(
+    monitor: &GpuMonitor,
+    cli: &Cli,
+    selected: &[u32],
+    mut persist: Option<history::HistoryFile>,
+    mut alerts: Option<alert::Alerts>,
+) -> anyhow::Result<()> {
+    use std::time::Duration;
+
+    // JSON goes to whichever stream the status line does not use
+    let mut out = match cli.status {
+        Some(OutputStream::Stdout) => OutputStream::Stderr.writer(),
+        _ => OutputStream::Stdout.writer(),
+    };
+    let mut status = cli.status.map(OutputStream::writer);
+    let mut load = cli
+        .detect_load
+        .then(|| load::LoadDetector::new(cli.load_threshold, cli.load_min_duration));
+    #[cfg(unix)]
+    let mut server = cli.socket.as_deref().map(socket::SocketServer::bind).transpose()?;
+    #[cfg(not(unix))]
+    if cli.socket.is_some() {
+        anyhow::bail!("--socket is only supported on Unix");
+    }
+
+    // Samples on fixed ticks, so the stream does not drift by the time spent writing
+    for snapshot in monitor.samples(Duration::from_millis(cli.interval)) {
+        let snapshot = snapshot?;
+        let mut gpus = snapshot.gpus;
+        select::retain(&mut gpus, selected);
+        if cli.stable {
+            stabilize(&mut gpus);
+        }
+        if cli.trace_samples {
+            trace_samples(&gpus, snapshot.latency);
+        }
+        if let Some(persist) = persist.as_mut() {
+            persist.record(&gpus)?;
+        }
+        if let Some(alerts) = alerts.as_mut() {
+            alerts.check(&gpus);
+        }
+        if let Some(load) = load.as_mut() {
+            // On stderr so the JSON stream stays parseable
+            for event in load.update(&gpus) {
+                eprintln!("{}", event);
+            }
+        }
+        let line = serde_json::to_string(&export::gpus_to_json(&gpus, &cli.fields, cli.json_options())?)?;
+        #[cfg(unix)]
+        let served = server.as_mut().map(|server| server.broadcast(&line)).is_some();
+        #[cfg(not(unix))]
+        let served = false;
+        if !served {
+            writeln!(out, "{}", line)?;
+            out.flush()?;
+        }
+        if let Some(status) = status.as_mut() {
+            // Overwrite the previous status line in place
+            write!(status, "\r\x1b[2K{} │ sample {}ms", status_line(&gpus), snapshot.latency.as_millis())?;
+            status.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a line for every GPU that changed since the previous sample
+fn run_diff_watch(monitor: &GpuMonitor, interval: u64, selected: &[u32]) -> anyhow::Result<()> {
+    let mut differ = diff::Differ::new();
+    let mut out = std::io::stdout();
+    for snapshot in monitor.samples(std::time::Duration::from_millis(interval)) {
+        let mut gpus = snapshot?.gpus;
+        select::retain(&mut gpus, selected);
+        for line in differ.update(&gpus) {
+            writeln!(out, "{}", line)?;
+        }
+        out.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Put GPUs in index order and processes in PID order so repeated snapshots diff cleanly
+///
+/// JSON object keys need no extra handling: structs serialize in declaration
+/// order and `serde_json` maps are sorted by key.
+fn stabilize(gpus: &mut [GpuInfo]) {
+    gpus.sort_by_key(|gpu| gpu.device.index);
+    for gpu in gpus {
+        gpu.processes.sort_by_key(|p| p.pid);
+    }
+}
+
+/// One-line human-readable summary of all GPUs
+fn status_line(gpus: &[GpuInfo]) -> String {
+    gpus.iter()
+        .map(|gpu| {
+            format!(
+                "GPU{} {:>3}% {:>3}°C {:>4.0}W",
+                gpu.device.index,
+                gpu.metrics.gpu_utilization,
+                gpu.metrics.temperature,
+                gpu.metrics.power_watts()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" │ ")
+}
+
+/// Emit one structured tracing event per GPU for the current sample
+///
+/// `latency` is how long the sample took to acquire, to spot a slow driver.
+fn trace_samples(gpus: &[GpuInfo], latency: std::time::Duration) {
+    for gpu in gpus {
+        tracing::info!(
+            target: SAMPLE_TARGET,
+            gpu = gpu.device.index,
+            util = gpu.metrics.gpu_utilization,
+            mem_used = gpu.memory.used,
+            temp = gpu.metrics.temperature,
+            power = gpu.metrics.power_watts(),
+            sampled_at = gpu.sampled_at,
+            latency_ms = latency.as_millis() as u64,
+            "sample"
+        );
+    }
+}
+
+/// Build the TUI application from the display flags
+fn tui_app(cli: &Cli, selected: Vec<u32>) -> anyhow::Result<app::App> {
+    let charts: [ChartMetric; 2] = cli
+        .chart
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("--chart expects exactly two metrics, e.g. util,temp"))?;
+    let mut app = app::App::new(cli.interval, charts, cli.history);
+    app.view = cli.view;
+    app.proc_columns = cli.proc_columns.clone();
+    app.smooth = cli.smooth;
+    app.stable = cli.stable;
+    app.selected = selected;
+    app.trace_samples = cli.trace_samples;
+    Ok(app)
+}
+
+/// Run interactive TUI
+fn run_tui(monitor: &GpuMonitor, mut app: app::App) -> anyhow::Result<()> {
+    let mut terminal = tui::init()?;
+    let result = app.run(&mut terminal, monitor);
+    tui::restore()?;
+    result
+}
+
+/// Format video engine utilization, showing N/A if the engine is absent
+fn format_engine(utilization: Option<u32>) -> String {
+    utilization
+        .map(|u| format!("{}%", u))
+        .unwrap_or_else(|| "N/A".to_string())
+}
+
+/// Format a clock as "current/base/max MHz", using `-` for unknown values
+fn format_clock_levels(current: u32, base: Option<u32>, max: Option<u32>) -> String {
+    let level = |v: Option<u32>| v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+    format!("{}/{}/{} MHz", current, level(base), level(max))
+}
+
+/// Clamp refresh interval to the supported minimum
+fn clamp_interval(interval: u64) -> u64 {
+    interval.max(MIN_INTERVAL_MS)
+}
+
+/// Truncate string to max length in characters, so multi-byte text is never split
+fn truncate_str(s: &str, max_len: usize) -> String {
+    if s.chars().count() <= max_len {
+        s.to_string()
+    } else {
+        format!("{}...", s.chars().take(max_len.saturating_sub(3)).collect::<String>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_interval() {
+        assert_eq!(clamp_interval(0), MIN_INTERVAL_MS);
+        assert_eq!(clamp_interval(10), MIN_INTERVAL_MS);
+        assert_eq!(clamp_interval(MIN_INTERVAL_MS), MIN_INTERVAL_MS);
+        assert_eq!(clamp_interval(1000), 1000);
+    }
+
+    #[test]
+    fn test_truncate_str() {
+        let sensors = "gpu 33°C  vcd_board 36°C  vcd_inlet 103°C  vcd_outlet 77°C";
+        let truncated = truncate_str(sensors, 45);
+        assert_eq!(truncated.chars().count(), 45);
+        assert!(truncated.ends_with("...") && truncated.starts_with("gpu 33°C"));
+        assert_eq!(truncate_str("gpu 33°C", 45), "gpu 33°C");
+    }
+}
@@ -0,0 +1,113 @@
+//! GPU selection by index, UUID or name pattern (`--gpu`)
+
+use gpu_monitor_core::{DeviceInfo, GpuInfo};
+
+/// Resolve `--gpu` selectors to device indices
+///
+/// Each selector is tried as an index, then as a UUID prefix (`GPU-1a2b`),
+/// then as a name pattern. Names are compared case-insensitively ignoring
+/// spaces, so `rtx4090` matches "NVIDIA GeForce RTX 4090"; a pattern without
+/// `*` matches anywhere in the name. A selector that matches nothing is an error.
+pub fn resolve(selectors: &[String], devices: &[DeviceInfo]) -> anyhow::Result<Vec<u32>> {
+    let mut indices = Vec::new();
+    for selector in selectors {
+        let matched = matches(selector, devices);
+        if matched.is_empty() {
+            let available: Vec<_> = devices
+                .iter()
+                .map(|d| format!("{}: {} ({})", d.index, d.name, d.uuid))
+                .collect();
+            anyhow::bail!(
+                "--gpu '{}' matches no GPU; available: {}",
+                selector,
+                available.join(", ")
+            );
+        }
+        for index in matched {
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+    }
+    Ok(indices)
+}
+
+/// Indices of the devices matched by one selector
+fn matches(selector: &str, devices: &[DeviceInfo]) -> Vec<u32> {
+    let selector = selector.trim();
+    if let Ok(index) = selector.parse::<u32>() {
+        return devices.iter().filter(|d| d.index == index).map(|d| d.index).collect();
+    }
+
+    let by_uuid: Vec<_> = devices
+        .iter()
+        .filter(|d| d.uuid.to_lowercase().starts_with(&selector.to_lowercase()))
+        .map(|d| d.index)
+        .collect();
+    if !by_uuid.is_empty() {
+        return by_uuid;
+    }
+
+    let pattern = normalize(selector);
+    let pattern = if pattern.contains('*') { pattern } else { format!("*{}*", pattern) };
+    devices
+        .iter()
+        .filter(|d| glob_match(&pattern, &normalize(&d.name)) || glob_match(&pattern, &d.uuid.to_lowercase()))
+        .map(|d| d.index)
+        .collect()
+}
+
+/// Lowercase and drop spaces so `RTX4090` and `RTX 4090` compare equal
+fn normalize(s: &str) -> String {
+    s.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect()
+}
+
+/// Match a pattern where `*` stands for any run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<_> = parts.collect();
+    // Without a `*` the whole text must match
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Keep only the selected GPUs; an empty selection keeps all of them
+pub fn retain(gpus: &mut Vec<GpuInfo>, selected: &[u32]) {
+    if !selected.is_empty() {
+        gpus.retain(|gpu| selected.contains(&gpu.device.index));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*rtx4090*", "nvidiageforcertx4090"));
+        assert!(glob_match("nvidia*a100*", "nvidiaa100-sxm4-80gb"));
+        assert!(glob_match("a100", "a100"));
+        assert!(!glob_match("a100", "a100-sxm4"));
+        assert!(!glob_match("*h100*", "nvidiaa100"));
+        // The suffix must not reuse characters consumed by the prefix
+        assert!(!glob_match("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("NVIDIA GeForce RTX 4090"), "nvidiageforcertx4090");
+        assert_eq!(normalize("RTX4090"), "rtx4090");
+    }
+}
@@ -0,0 +1,128 @@
+//! NDJSON sample stream over a Unix domain socket (`--socket`)
+
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long a write may block before a client that stopped reading is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Socket server that sends every sample line to all connected clients
+///
+/// Runs on the sampling thread: new clients are accepted before each
+/// broadcast, and a client that disconnects or stops reading is dropped
+/// without affecting the others.
+pub struct SocketServer {
+    path: PathBuf,
+    listener: UnixListener,
+    clients: Vec<UnixStream>,
+}
+
+impl SocketServer {
+    /// Listen on a socket path, replacing a stale socket left by a previous run
+    ///
+    /// Refuses to replace anything that is not a socket.
+    pub fn bind(path: &Path) -> anyhow::Result<Self> {
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                anyhow::bail!("{} exists and is not a socket", path.display());
+            }
+            // A live server still accepts connections; refuse to steal its path
+            if UnixStream::connect(path).is_ok() {
+                anyhow::bail!("{} is in use by another server", path.display());
+            }
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            listener,
+            clients: Vec::new(),
+        })
+    }
+
+    /// Send one line to every client
+    pub fn broadcast(&mut self, line: &str) {
+        self.accept_pending();
+        self.clients.retain_mut(|client| {
+            match client.write_all(line.as_bytes()).and_then(|()| client.write_all(b"\n")) {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::debug!("Dropping socket client: {}", e);
+                    false
+                }
+            }
+        });
+    }
+
+    /// Accept every connection waiting in the backlog
+    fn accept_pending(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((client, _)) => {
+                    // Writes block with a timeout, so a stalled client cannot stall sampling
+                    if client.set_nonblocking(false).is_ok()
+                        && client.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok()
+                    {
+                        self.clients.push(client);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    tracing::warn!("Failed to accept socket client: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SocketServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn test_broadcast() {
+        let path = std::env::temp_dir().join(format!("gpu-monitor-test-{}.sock", std::process::id()));
+        let mut server = SocketServer::bind(&path).unwrap();
+
+        let first = UnixStream::connect(&path).unwrap();
+        let second = UnixStream::connect(&path).unwrap();
+        server.broadcast("{\"a\":1}");
+        let mut reader = BufReader::new(&first);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"a\":1}\n");
+
+        // A client that disconnects is dropped, the rest keep receiving
+        drop(second);
+        server.broadcast("{\"a\":2}");
+        server.broadcast("{\"a\":3}");
+        assert_eq!(server.clients.len(), 1);
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"a\":2}\n");
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_bind_over_file() {
+        let path = std::env::temp_dir().join(format!("gpu-monitor-test-{}.txt", std::process::id()));
+        std::fs::write(&path, "notes").unwrap();
+        assert!(SocketServer::bind(&path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "notes");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
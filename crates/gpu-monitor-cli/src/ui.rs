@@ -1,271 +1,781 @@
-//! UI rendering for TUI
-
-use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    text::{Line, Span},
-    widgets::{
-        Block, Borders, Paragraph, Row, Sparkline, Table,
-    },
-    Frame,
-};
-
-use crate::app::App;
-
-/// Main draw function
-pub fn draw(frame: &mut Frame, app: &App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([
-            Constraint::Length(3), // Header
-            Constraint::Min(10),   // GPU cards
-            Constraint::Length(1), // Footer
-        ])
-        .split(frame.area());
-
-    // Header
-    draw_header(frame, chunks[0]);
-
-    // GPU cards (one per GPU)
-    if !app.gpus.is_empty() {
-        let gpu_constraints: Vec<Constraint> = app
-            .gpus
-            .iter()
-            .map(|_| Constraint::Min(12)) // Compact height
-            .collect();
-
-        let gpu_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(gpu_constraints)
-            .split(chunks[1]);
-
-        for (i, gpu) in app.gpus.iter().enumerate() {
-            if i < gpu_chunks.len() {
-                let history = app.gpu_history.get(i).map(|h| h.as_slice()).unwrap_or(&[]);
-                let mem_history = app.memory_history.get(i).map(|h| h.as_slice()).unwrap_or(&[]);
-                draw_gpu_card(frame, gpu_chunks[i], gpu, history, mem_history, app.process_scroll);
-            }
-        }
-    } else {
-        let msg = Paragraph::new("No GPU data available. Make sure NVIDIA drivers are installed.")
-            .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title("GPU Monitor"));
-        frame.render_widget(msg, chunks[1]);
-    }
-
-    // Footer
-    draw_footer(frame, chunks[2]);
-}
-
-/// Draw header
-fn draw_header(frame: &mut Frame, area: Rect) {
-    let header = Block::default()
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan))
-        .title(Span::styled(
-            " GPU Monitor ",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        ));
-
-    let inner = header.inner(area);
-    frame.render_widget(header, area);
-
-    let text = Paragraph::new(Line::from(vec![
-        Span::styled("Real-time GPU monitoring", Style::default().fg(Color::White)),
-        Span::raw(" │ "),
-        Span::styled("Press ", Style::default().fg(Color::DarkGray)),
-        Span::styled("q", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-        Span::styled(" to quit", Style::default().fg(Color::DarkGray)),
-    ]));
-    frame.render_widget(text, inner);
-}
-
-/// Draw footer
-fn draw_footer(frame: &mut Frame, area: Rect) {
-    let footer = Paragraph::new(Line::from(vec![
-        Span::styled("↑/↓", Style::default().fg(Color::Yellow)),
-        Span::raw(" scroll │ "),
-        Span::styled("q", Style::default().fg(Color::Yellow)),
-        Span::raw(" quit"),
-    ]))
-    .style(Style::default().fg(Color::DarkGray));
-    frame.render_widget(footer, area);
-}
-
-/// Draw a single GPU card
-fn draw_gpu_card(
-    frame: &mut Frame,
-    area: Rect,
-    gpu: &gpu_monitor_core::GpuInfo,
-    gpu_history: &[u64],
-    mem_history: &[u64],
-    process_scroll: u16,
-) {
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Blue))
-        .title(Span::styled(
-            format!(" GPU {}: {} ", gpu.device.index, gpu.device.name),
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        ));
-
-    let inner = block.inner(area);
-    frame.render_widget(block, area);
-
-    // Split into left (metrics) and right (processes)
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
-        .split(inner);
-
-    // Left side: metrics
-    draw_metrics(frame, chunks[0], gpu, gpu_history, mem_history);
-
-    // Right side: processes
-    draw_processes(frame, chunks[1], &gpu.processes, process_scroll);
-}
-
-/// Draw GPU metrics
-fn draw_metrics(
-    frame: &mut Frame,
-    area: Rect,
-    gpu: &gpu_monitor_core::GpuInfo,
-    gpu_history: &[u64],
-    mem_history: &[u64],
-) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(1), // Info row
-            Constraint::Length(1), // Spacer
-            Constraint::Length(3), // GPU Chart
-            Constraint::Length(1), // Spacer
-            Constraint::Length(3), // Memory Chart
-        ])
-        .split(area);
-
-    // Info Row
-    let temp_color = match gpu.metrics.temperature_status() {
-        gpu_monitor_core::metrics::TemperatureStatus::Cool => Color::Green,
-        gpu_monitor_core::metrics::TemperatureStatus::Normal => Color::Blue,
-        gpu_monitor_core::metrics::TemperatureStatus::Warm => Color::Yellow,
-        gpu_monitor_core::metrics::TemperatureStatus::Hot => Color::Red,
-    };
-
-    let info_text = Line::from(vec![
-        Span::raw("Temp: "),
-        Span::styled(format!("{}°C", gpu.metrics.temperature), Style::default().fg(temp_color)),
-        Span::raw("  Power: "),
-        Span::styled(format!("{:.0}W", gpu.metrics.power_watts()), Style::default().fg(Color::Yellow)),
-        Span::raw("  Fan: "),
-        Span::styled(
-            format!("{}%", gpu.metrics.fan_speed.map(|f| f.to_string()).unwrap_or_else(|| "N/A".to_string())),
-            Style::default().fg(Color::Cyan)
-        ),
-        Span::raw("  Clock: "),
-        Span::styled(format!("{}MHz", gpu.metrics.clock_graphics), Style::default().fg(Color::Magenta)),
-    ]);
-    frame.render_widget(Paragraph::new(info_text), chunks[0]);
-
-    // GPU Chart Section
-    let gpu_color = if gpu.metrics.gpu_utilization > 80 {
-        Color::Red
-    } else if gpu.metrics.gpu_utilization > 50 {
-        Color::Yellow
-    } else {
-        Color::Green
-    };
-
-    // Title with real-time value
-    let gpu_title = format!("GPU Load: {}%", gpu.metrics.gpu_utilization);
-
-    let gpu_sparkline = Sparkline::default()
-        .block(Block::default().title(gpu_title).borders(Borders::NONE))
-        .data(gpu_history)
-        .max(100)
-        .style(Style::default().fg(gpu_color));
-    frame.render_widget(gpu_sparkline, chunks[2]);
-
-    // Memory Chart Section
-    let mem_percent = gpu.memory.usage_percent() as u16;
-    let mem_color = if mem_percent > 80 {
-        Color::Red
-    } else if mem_percent > 50 {
-        Color::Yellow
-    } else {
-        Color::Cyan
-    };
-
-    // Title with real-time value
-    let mem_title = format!(
-        "Memory: {:.1} / {:.1} GiB ({:.0}%)",
-        gpu.memory.used_gib(),
-        gpu.memory.total_gib(),
-        gpu.memory.usage_percent()
-    );
-
-    let mem_sparkline = Sparkline::default()
-        .block(Block::default().title(mem_title).borders(Borders::NONE))
-        .data(mem_history)
-        .max(100)
-        .style(Style::default().fg(mem_color));
-    frame.render_widget(mem_sparkline, chunks[4]);
-}
-
-/// Draw GPU processes
-fn draw_processes(
-    frame: &mut Frame,
-    area: Rect,
-    processes: &[gpu_monitor_core::GpuProcess],
-    scroll: u16,
-) {
-    let header = Row::new(vec!["PID", "Name", "Mem", "Type"])
-        .style(Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan));
-
-    let rows: Vec<Row> = processes
-        .iter()
-        .skip(scroll as usize)
-        .map(|p| {
-            Row::new(vec![
-                p.pid.to_string(),
-                truncate_str(&p.name, 15),
-                format!("{}M", p.gpu_memory_mib()),
-                p.process_type.short_label().to_string(),
-            ])
-        })
-        .collect();
-
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(7),
-            Constraint::Min(10),
-            Constraint::Length(8),
-            Constraint::Length(6),
-        ],
-    )
-    .header(header)
-    .block(
-        Block::default()
-            .borders(Borders::LEFT)
-            .border_style(Style::default().fg(Color::DarkGray))
-            .title(format!("Processes ({})", processes.len())),
-    )
-    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
-
-    frame.render_widget(table, area);
-}
-
-/// Truncate string to max length
-fn truncate_str(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len - 3])
-    }
-}
+//! UI rendering for TUI
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Cell, Clear, Gauge, Paragraph, Row, Sparkline, Table,
+    },
+    Frame,
+};
+
+use gpu_monitor_core::ClockState;
+use std::time::Duration;
+
+use crate::app::{App, ChartMetric, GpuHistory, ProcColumn, View};
+
+/// Main draw function
+pub fn draw(frame: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Header
+            Constraint::Min(10),   // GPU cards
+            Constraint::Length(1), // Footer
+        ])
+        .split(frame.area());
+
+    // Header
+    draw_header(frame, chunks[0], &app.gpus);
+
+    // Merged process table (`processes --watch`) or GPU cards (one per GPU)
+    if app.process_view {
+        draw_process_view(frame, chunks[1], app);
+    } else if !app.gpus.is_empty() {
+        let gpu_constraints: Vec<Constraint> = app
+            .gpus
+            .iter()
+            .map(|_| Constraint::Min(12)) // Compact height
+            .collect();
+
+        let gpu_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(gpu_constraints)
+            .split(chunks[1]);
+
+        for (i, gpu) in app.gpus.iter().enumerate() {
+            if i < gpu_chunks.len() {
+                let history = app.history.get(i);
+                draw_gpu_card(frame, gpu_chunks[i], gpu, history, app, i == app.focused_gpu);
+            }
+        }
+    } else {
+        let msg = Paragraph::new("No GPU data available. Make sure NVIDIA drivers are installed.")
+            .style(Style::default().fg(Color::Yellow))
+            .block(Block::default().borders(Borders::ALL).title("GPU Monitor"));
+        frame.render_widget(msg, chunks[1]);
+    }
+
+    // Footer
+    draw_footer(frame, chunks[2], app);
+
+    // Overlays
+    if app.show_help {
+        draw_help(frame);
+    }
+    if app.show_stats {
+        draw_session_stats(frame, app);
+    }
+}
+
+/// Draw header
+fn draw_header(frame: &mut Frame, area: Rect, gpus: &[gpu_monitor_core::GpuInfo]) {
+    let header = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(Span::styled(
+            " GPU Monitor ",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+    let inner = header.inner(area);
+    frame.render_widget(header, area);
+
+    let mut spans = vec![
+        Span::styled("Real-time GPU monitoring", Style::default().fg(Color::White)),
+        Span::raw(" │ "),
+        Span::styled("Press ", Style::default().fg(Color::DarkGray)),
+        Span::styled("q", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::styled(" to quit", Style::default().fg(Color::DarkGray)),
+    ];
+    if !gpus.is_empty() {
+        spans.push(Span::raw(" │"));
+    }
+    // Health dot per GPU: green if nothing needs attention, red otherwise
+    for gpu in gpus {
+        let color = if gpu.is_healthy() { Color::Green } else { Color::Red };
+        spans.push(Span::styled(" ●", Style::default().fg(color)));
+        spans.push(Span::raw(format!("GPU{}", gpu.device.index)));
+    }
+    let text = Paragraph::new(Line::from(spans));
+    frame.render_widget(text, inner);
+}
+
+/// Sample latency above which the footer highlights a slow driver
+const SLOW_SAMPLE: Duration = Duration::from_millis(100);
+
+/// Draw footer
+fn draw_footer(frame: &mut Frame, area: Rect, app: &App) {
+    let mut spans = vec![
+        Span::styled("↑/↓", Style::default().fg(Color::Yellow)),
+        Span::raw(" select │ "),
+    ];
+    if app.process_view {
+        spans.push(Span::styled("o", Style::default().fg(Color::Yellow)));
+        spans.push(Span::raw(" sort │ "));
+    } else {
+        spans.push(Span::styled("Tab", Style::default().fg(Color::Yellow)));
+        spans.push(Span::raw(" next GPU │ "));
+    }
+    spans.extend([
+        Span::styled("?", Style::default().fg(Color::Yellow)),
+        Span::raw(" help │ "),
+        Span::styled("q", Style::default().fg(Color::Yellow)),
+        Span::raw(" quit"),
+    ]);
+    if let Some(latency) = app.sample_latency {
+        let style = if latency >= SLOW_SAMPLE { Style::default().fg(Color::Yellow) } else { Style::default() };
+        spans.push(Span::raw(" │ "));
+        spans.push(Span::styled(format!("sample: {}ms", latency.as_millis()), style));
+    }
+    if app.paused {
+        spans.push(Span::raw(" │ "));
+        spans.push(Span::styled(
+            "PAUSED",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    let footer = Paragraph::new(Line::from(spans)).style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(footer, area);
+}
+
+/// Keyboard shortcuts shown in the help overlay
+const SHORTCUTS: &[(&str, &str)] = &[
+    ("q / Esc", "Quit"),
+    ("↑ / k", "Select previous process"),
+    ("↓ / j", "Select next process"),
+    ("Tab", "Focus next GPU's processes"),
+    ("o", "Cycle sort (processes --watch)"),
+    ("p / Space", "Pause or resume refresh"),
+    ("r", "Refresh now"),
+    ("g", "Toggle gauges and charts"),
+    ("s", "Toggle session statistics"),
+    ("?", "Toggle this help"),
+];
+
+/// Draw the help overlay centered over the current view
+fn draw_help(frame: &mut Frame) {
+    let lines: Vec<Line> = SHORTCUTS
+        .iter()
+        .map(|(key, action)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>10}", key),
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  "),
+                Span::raw(*action),
+            ])
+        })
+        .collect();
+
+    let area = centered_rect(40, lines.len() as u16 + 2, frame.area());
+    let help = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(" Help "),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(help, area);
+}
+
+/// Draw per-GPU throttling statistics for this session
+fn draw_session_stats(frame: &mut Frame, app: &App) {
+    let mut lines: Vec<Line> = Vec::new();
+    for (gpu, history) in app.gpus.iter().zip(&app.history) {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("GPU {}", gpu.device.index),
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(
+                "  throttled {}/{} samples",
+                history.throttled_samples, history.samples
+            )),
+        ]));
+        for (reason, count) in history.throttle_counts() {
+            lines.push(Line::from(format!(
+                "  {:>5}/{:<5} {}",
+                count,
+                history.samples,
+                reason.label()
+            )));
+        }
+    }
+
+    let area = centered_rect(50, lines.len() as u16 + 2, frame.area());
+    let stats = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(" Session Stats "),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(stats, area);
+}
+
+/// Get a rectangle of the given size centered in `area`
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Draw a single GPU card
+fn draw_gpu_card(
+    frame: &mut Frame,
+    area: Rect,
+    gpu: &gpu_monitor_core::GpuInfo,
+    history: Option<&GpuHistory>,
+    app: &App,
+    focused: bool,
+) {
+    // With several GPUs, the highlighted border shows which card the arrow keys act on
+    let border = if focused && app.gpus.len() > 1 { Color::Cyan } else { Color::Blue };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border))
+        .title(Span::styled(
+            format!(
+                " GPU {}: {}{} ",
+                gpu.device.index,
+                gpu.device.name,
+                if gpu.driving_display { " [display]" } else { "" }
+            ),
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+    let mut inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    // Banner an operator must not miss: memory needs a reset to finish retiring
+    if gpu.memory_retirement_pending() && inner.height > 1 {
+        let banner = Paragraph::new(Line::from(Span::styled(
+            " MEMORY HEALTH: pending retirement (reset GPU to apply) ",
+            Style::default()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+        )));
+        frame.render_widget(banner, Rect { height: 1, ..inner });
+        inner.y += 1;
+        inner.height -= 1;
+    }
+
+    // Split into left (metrics) and right (processes)
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(inner);
+
+    // Left side: metrics
+    draw_metrics(frame, chunks[0], gpu, history, app);
+
+    // Right side: processes
+    if focused {
+        draw_processes(frame, chunks[1], gpu, &app.proc_columns, app.process_scroll, app.selected_process);
+    } else {
+        draw_processes(frame, chunks[1], gpu, &app.proc_columns, 0, None);
+    }
+}
+
+/// Draw GPU metrics
+fn draw_metrics(
+    frame: &mut Frame,
+    area: Rect,
+    gpu: &gpu_monitor_core::GpuInfo,
+    history: Option<&GpuHistory>,
+    app: &App,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Info row
+            Constraint::Length(1), // Throttle diagnostic
+            Constraint::Length(3), // First chart
+            Constraint::Length(1), // Spacer
+            Constraint::Length(3), // Second chart
+            Constraint::Length(1), // Spacer
+            Constraint::Length(3), // Power chart (if space allows)
+        ])
+        .split(area);
+
+    // Info Row
+    let temp_color = temperature_color(gpu);
+    let (temperature_rate, power_rate) =
+        history.map(|h| (h.temperature_rate, h.power_rate)).unwrap_or_default();
+
+    // Other sensors, e.g. memory, which limits clocks on some cards
+    let sensors: String = gpu
+        .metrics
+        .temperatures
+        .iter()
+        .filter(|(sensor, _)| *sensor != "gpu")
+        .map(|(sensor, temp)| format!(" {} {}°C", sensor, temp))
+        .collect();
+
+    let mut info_text = Line::from(vec![
+        Span::raw("Temp: "),
+        Span::styled(format!("{}°C", gpu.metrics.temperature), Style::default().fg(temp_color)),
+        Span::styled(format!(" ({:+.1}°C/s)", temperature_rate), Style::default().fg(Color::DarkGray)),
+        Span::raw(sensors),
+        Span::raw("  Power: "),
+        Span::styled(format!("{:.0}W", gpu.metrics.power_watts()), Style::default().fg(Color::Yellow)),
+        Span::styled(format!(" ({:+.0}W/s)", power_rate), Style::default().fg(Color::DarkGray)),
+        Span::raw("  Fan: "),
+        Span::styled(
+            match gpu.metrics.fan_speed {
+                _ if !gpu.device.has_fan => "passive".to_string(),
+                // Show every fan so one stuck fan among several is visible
+                _ if gpu.metrics.fan_speeds.len() > 1 => gpu
+                    .metrics
+                    .fan_speeds
+                    .iter()
+                    .map(|fan| format!("{}%", fan))
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                Some(fan) => format!("{}%", fan),
+                None => "N/A".to_string(),
+            },
+            Style::default().fg(Color::Cyan)
+        ),
+        Span::raw("  Clock: "),
+        Span::styled(format!("{}MHz", gpu.metrics.clock_graphics), Style::default().fg(Color::Magenta)),
+    ]);
+    if gpu.device.clock_state == ClockState::ApplicationLocked {
+        info_text.push_span(Span::styled(
+            format!(" (locked @ {}MHz)", gpu.device.app_clock_graphics.unwrap_or_default()),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    frame.render_widget(Paragraph::new(info_text), chunks[0]);
+
+    // Explain reduced clocks right below the raw numbers
+    let mut throttle_line = Line::default();
+    if gpu.power_brake_active() {
+        throttle_line.push_span(Span::styled(
+            " POWER BRAKE: PSU/VRM limit ",
+            Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+        throttle_line.push_span(Span::raw(" "));
+    }
+    if let Some(diagnostic) = gpu.throttle_diagnostic() {
+        throttle_line.push_span(Span::styled(
+            format!("⚠ {}", diagnostic),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    frame.render_widget(Paragraph::new(throttle_line), chunks[1]);
+
+    if app.view == View::Gauges {
+        let area = chunks[2].union(chunks[6]);
+        draw_gauges(frame, area, gpu);
+        return;
+    }
+
+    let (charts, smooth) = (app.charts, app.smooth);
+    draw_chart(frame, chunks[2], gpu, history, charts[0], smooth);
+    draw_chart(frame, chunks[4], gpu, history, charts[1], smooth);
+
+    // Power chart scaled to the power limit, unless already selected
+    if chunks[6].height >= 3 && !charts.contains(&ChartMetric::Power) {
+        draw_chart(frame, chunks[6], gpu, history, ChartMetric::Power, smooth);
+    }
+}
+
+/// Title with the real-time value, scale maximum, current value and color of a metric
+fn metric_display(gpu: &gpu_monitor_core::GpuInfo, metric: ChartMetric) -> (String, u64, u64, Color) {
+    match metric {
+        ChartMetric::Util => (
+            format!("GPU Load: {}%", gpu.metrics.gpu_utilization),
+            100,
+            gpu.metrics.gpu_utilization as u64,
+            load_color(gpu.metrics.gpu_utilization as u64, Color::Green),
+        ),
+        ChartMetric::Mem => (
+            // Capacity and bandwidth load are easily confused, so label both
+            format!(
+                "Memory Used: {:.1} / {:.1} GiB ({:.0}%)  Bandwidth: {}%",
+                gpu.memory.used_gib(),
+                gpu.memory.total_gib(),
+                gpu.memory.usage_percent(),
+                gpu.metrics.memory_controller_load()
+            ),
+            100,
+            gpu.memory.usage_percent() as u64,
+            load_color(gpu.memory.usage_percent() as u64, Color::Cyan),
+        ),
+        ChartMetric::Temp => (
+            format!("Temperature: {}°C", gpu.metrics.temperature),
+            100,
+            gpu.metrics.temperature as u64,
+            temperature_color(gpu),
+        ),
+        ChartMetric::Power => {
+            let limit = u64::from(gpu.device.power_limit.max(1));
+            let percent = gpu.metrics.power_watts() as u64 * 100 / limit;
+            // Running at the cap means the card is power throttling
+            let color = if percent >= 95 {
+                Color::Red
+            } else if percent > 80 {
+                Color::Yellow
+            } else {
+                Color::Magenta
+            };
+            (
+                format!("Power: {:.0} / {} W", gpu.metrics.power_watts(), gpu.device.power_limit),
+                limit,
+                gpu.metrics.power_watts() as u64,
+                color,
+            )
+        }
+    }
+}
+
+/// Draw instantaneous bar gauges for every metric
+fn draw_gauges(frame: &mut Frame, area: Rect, gpu: &gpu_monitor_core::GpuInfo) {
+    const METRICS: [ChartMetric; 4] =
+        [ChartMetric::Util, ChartMetric::Mem, ChartMetric::Temp, ChartMetric::Power];
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2); METRICS.len()])
+        .split(area);
+
+    for (metric, row) in METRICS.into_iter().zip(rows.iter()) {
+        let (title, max, value, color) = metric_display(gpu, metric);
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(color).bg(Color::Black))
+            .ratio((value as f64 / max.max(1) as f64).min(1.0))
+            .label(title);
+        // Leave a blank line between gauges
+        frame.render_widget(gauge, Rect { height: 1, ..*row });
+    }
+}
+
+/// Draw a sparkline chart for the selected metric
+fn draw_chart(
+    frame: &mut Frame,
+    area: Rect,
+    gpu: &gpu_monitor_core::GpuInfo,
+    history: Option<&GpuHistory>,
+    metric: ChartMetric,
+    smooth: bool,
+) {
+    // Fit the whole history window into the chart width
+    let samples = history.map(|h| h.get(metric)).unwrap_or(&[]);
+    let data = if smooth && samples.len() < area.width as usize {
+        interpolate(samples, area.width as usize)
+    } else {
+        downsample(samples, area.width as usize)
+    };
+
+    let (title, max, _, color) = metric_display(gpu, metric);
+    let sparkline = Sparkline::default()
+        .block(Block::default().title(title).borders(Borders::NONE))
+        .data(&data)
+        .max(max)
+        .style(Style::default().fg(color));
+    frame.render_widget(sparkline, area);
+}
+
+/// Average samples into at most `width` buckets, preserving the full time range
+fn downsample(data: &[u64], width: usize) -> Vec<u64> {
+    if width == 0 || data.len() <= width {
+        return data.to_vec();
+    }
+
+    (0..width)
+        .map(|i| {
+            let start = i * data.len() / width;
+            let end = (i + 1) * data.len() / width;
+            let bucket = &data[start..end];
+            bucket.iter().sum::<u64>() / bucket.len() as u64
+        })
+        .collect()
+}
+
+/// Linearly interpolate samples to `width` points for rendering
+fn interpolate(data: &[u64], width: usize) -> Vec<u64> {
+    if data.len() < 2 || width <= data.len() {
+        return data.to_vec();
+    }
+
+    let last = (data.len() - 1) as f64;
+    (0..width)
+        .map(|i| {
+            let pos = i as f64 * last / (width - 1) as f64;
+            let lo = pos.floor() as usize;
+            let hi = (lo + 1).min(data.len() - 1);
+            let frac = pos - lo as f64;
+            (data[lo] as f64 * (1.0 - frac) + data[hi] as f64 * frac).round() as u64
+        })
+        .collect()
+}
+
+/// Color for a percentage load value, using `base` when below 50%
+fn load_color(percent: u64, base: Color) -> Color {
+    if percent > 80 {
+        Color::Red
+    } else if percent > 50 {
+        Color::Yellow
+    } else {
+        base
+    }
+}
+
+/// Color for the current temperature status
+fn temperature_color(gpu: &gpu_monitor_core::GpuInfo) -> Color {
+    // Proximity to the card's own slowdown threshold beats fixed bands
+    if let Some(margin) = gpu.thermal_margin() {
+        return match margin {
+            ..=5 => Color::Red,
+            6..=15 => Color::Yellow,
+            _ => Color::Green,
+        };
+    }
+    match gpu.metrics.temperature_status() {
+        gpu_monitor_core::metrics::TemperatureStatus::Cool => Color::Green,
+        gpu_monitor_core::metrics::TemperatureStatus::Normal => Color::Blue,
+        gpu_monitor_core::metrics::TemperatureStatus::Warm => Color::Yellow,
+        gpu_monitor_core::metrics::TemperatureStatus::Hot => Color::Red,
+    }
+}
+
+/// Get the color for a process type, so the workload mix stands out
+fn process_type_color(process_type: gpu_monitor_core::ProcessType) -> Color {
+    use gpu_monitor_core::ProcessType;
+    match process_type {
+        ProcessType::Compute => Color::Green,
+        ProcessType::Graphics => Color::Blue,
+        ProcessType::Mixed => Color::Magenta,
+        ProcessType::Unknown => Color::DarkGray,
+    }
+}
+
+/// Draw GPU processes
+fn draw_processes(
+    frame: &mut Frame,
+    area: Rect,
+    gpu: &gpu_monitor_core::GpuInfo,
+    columns: &[ProcColumn],
+    scroll: u16,
+    selected: Option<usize>,
+) {
+    let area = draw_process_detail(frame, area, selected.and_then(|row| gpu.processes.get(row)));
+
+    let header = Row::new(columns.iter().map(|c| column_header(*c)))
+        .style(Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan));
+
+    let mut rows: Vec<Row> = gpu
+        .processes
+        .iter()
+        .enumerate()
+        .skip(scroll as usize)
+        .map(|(row, p)| {
+            let cells = Row::new(columns.iter().map(|c| {
+                let cell = Cell::from(column_cell(*c, p, gpu.memory.total));
+                match c {
+                    ProcColumn::Type => cell.style(Style::default().fg(process_type_color(p.process_type))),
+                    _ => cell,
+                }
+            }));
+            if selected == Some(row) {
+                cells.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                cells
+            }
+        })
+        .collect();
+    // Memory the processes do not account for, so the rows add up to the used memory
+    if let Some(overhead) = gpu.memory_overhead().filter(|o| *o > 0) {
+        rows.push(
+            Row::new(columns.iter().map(|c| match c {
+                ProcColumn::Name | ProcColumn::Cmdline => "other/reserved".to_string(),
+                ProcColumn::Mem => format!("{}M", overhead / (1024 * 1024)),
+                ProcColumn::MemPct if gpu.memory.total > 0 => {
+                    format!("{:.0}%", overhead as f64 * 100.0 / gpu.memory.total as f64)
+                }
+                _ => String::new(),
+            }))
+            .style(Style::default().fg(Color::DarkGray)),
+        );
+    }
+
+    let widths: Vec<Constraint> = columns.iter().map(|c| column_width(*c)).collect();
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::LEFT)
+                .border_style(Style::default().fg(Color::DarkGray))
+                .title(format!("Processes ({})", gpu.process_count)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    frame.render_widget(table, area);
+}
+
+/// Draw the full details of the selected process on the last line, returning the area left above it
+fn draw_process_detail(frame: &mut Frame, mut area: Rect, selected: Option<&gpu_monitor_core::GpuProcess>) -> Rect {
+    if let Some(p) = selected.filter(|_| area.height > 2) {
+        area.height -= 1;
+        let detail = format!(
+            " PID {} │ {} │ {} │ {}",
+            p.pid,
+            p.user,
+            p.runtime_seconds.map(crate::format::duration).unwrap_or_else(|| "-".to_string()),
+            p.cmdline.as_deref().unwrap_or(&p.name)
+        );
+        let line = Paragraph::new(detail).style(Style::default().fg(Color::White).bg(Color::DarkGray));
+        frame.render_widget(line, Rect { y: area.y + area.height, height: 1, ..area });
+    }
+    area
+}
+
+/// Draw the processes of all GPUs as one sortable table (`processes --watch`)
+fn draw_process_view(frame: &mut Frame, area: Rect, app: &App) {
+    let processes = app.merged_processes();
+    let area = draw_process_detail(frame, area, app.selected_process());
+
+    let header = Row::new(std::iter::once("GPU").chain(app.proc_columns.iter().map(|c| column_header(*c))))
+        .style(Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan));
+
+    let rows: Vec<Row> = processes
+        .iter()
+        .enumerate()
+        .skip(app.process_scroll as usize)
+        .map(|(row, (gpu, p))| {
+            let gpu_cell = Cell::from(gpu.device.index.to_string());
+            let cells = Row::new(std::iter::once(gpu_cell).chain(app.proc_columns.iter().map(|c| {
+                let cell = Cell::from(column_cell(*c, p, gpu.memory.total));
+                match c {
+                    ProcColumn::Type => cell.style(Style::default().fg(process_type_color(p.process_type))),
+                    _ => cell,
+                }
+            })));
+            if app.selected_process == Some(row) {
+                cells.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                cells
+            }
+        })
+        .collect();
+
+    let widths: Vec<Constraint> = std::iter::once(Constraint::Length(4))
+        .chain(app.proc_columns.iter().map(|c| column_width(*c)))
+        .collect();
+    let table = Table::new(rows, widths).header(header).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(
+                " Processes on {} GPUs ({}) │ sorted by {} ",
+                app.gpus.len(),
+                processes.len(),
+                app.process_sort.label()
+            )),
+    );
+
+    frame.render_widget(table, area);
+}
+
+/// Header label of a process table column
+fn column_header(column: ProcColumn) -> &'static str {
+    match column {
+        ProcColumn::Pid => "PID",
+        ProcColumn::Name => "Name",
+        ProcColumn::User => "User",
+        ProcColumn::Mem => "Mem",
+        ProcColumn::MemPct => "Mem%",
+        ProcColumn::Type => "Type",
+        ProcColumn::SmUtil => "SM%",
+        ProcColumn::Cmdline => "Command",
+        ProcColumn::Runtime => "Time",
+    }
+}
+
+/// Width of a process table column
+fn column_width(column: ProcColumn) -> Constraint {
+    match column {
+        ProcColumn::Pid => Constraint::Length(7),
+        ProcColumn::Name | ProcColumn::Cmdline => Constraint::Min(10),
+        ProcColumn::User => Constraint::Length(10),
+        ProcColumn::Mem => Constraint::Length(8),
+        ProcColumn::MemPct | ProcColumn::SmUtil => Constraint::Length(5),
+        ProcColumn::Type => Constraint::Length(6),
+        ProcColumn::Runtime => Constraint::Length(7),
+    }
+}
+
+/// Cell text of a process table column
+fn column_cell(column: ProcColumn, p: &gpu_monitor_core::GpuProcess, total_memory: u64) -> String {
+    match column {
+        ProcColumn::Pid => p.pid.to_string(),
+        ProcColumn::Name => truncate_str(&p.name, 15),
+        ProcColumn::User => truncate_str(&p.user, 10),
+        ProcColumn::Mem => format!("{}M", p.gpu_memory_mib()),
+        ProcColumn::MemPct => format!("{:.0}%", p.gpu_memory_percent(total_memory)),
+        ProcColumn::Type => p.process_type.short_label().to_string(),
+        ProcColumn::SmUtil => p
+            .sm_utilization
+            .map(|u| format!("{}%", u))
+            .unwrap_or_else(|| "-".to_string()),
+        ProcColumn::Cmdline => p.cmdline.clone().unwrap_or_else(|| p.name.clone()),
+        ProcColumn::Runtime => p
+            .runtime_seconds
+            .map(crate::format::duration)
+            .unwrap_or_else(|| "-".to_string()),
+    }
+}
+
+/// Truncate string to max length in characters, so multi-byte text is never split
+fn truncate_str(s: &str, max_len: usize) -> String {
+    if s.chars().count() <= max_len {
+        s.to_string()
+    } else {
+        format!("{}...", s.chars().take(max_len.saturating_sub(3)).collect::<String>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downsample() {
+        let data: Vec<u64> = (0..600).collect();
+        let buckets = downsample(&data, 40);
+        assert_eq!(buckets.len(), 40);
+        assert_eq!(buckets[0], 7); // average of 0..15
+        assert_eq!(buckets[39], 592); // average of 585..600
+
+        // Short histories are left untouched
+        assert_eq!(downsample(&[1, 2, 3], 40), vec![1, 2, 3]);
+    }
+
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(interpolate(&[0, 10, 20], 5), vec![0, 5, 10, 15, 20]);
+        assert_eq!(interpolate(&[7], 5), vec![7]);
+        assert_eq!(interpolate(&[1, 2, 3], 2), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_truncate_str() {
+        assert_eq!(truncate_str("josé.garcía", 10), "josé.ga...");
+        assert_eq!(truncate_str("josé", 10), "josé");
+    }
+}
@@ -0,0 +1,78 @@
+//! Fleet totals across all GPUs
+
+use serde::{Deserialize, Serialize};
+
+use crate::GpuInfo;
+
+/// Totals and averages over a set of GPUs
+///
+/// Field names are part of the `--summary --json` output and stay stable.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AggregateStats {
+    /// Number of GPUs
+    pub gpu_count: usize,
+    /// Mean GPU utilization percentage (0-100), 0 without GPUs
+    pub avg_utilization: f32,
+    /// Used memory summed over all GPUs, in bytes
+    pub total_memory_used: u64,
+    /// Memory summed over all GPUs, in bytes
+    pub total_memory: u64,
+    /// Power draw summed over all GPUs, in watts
+    pub total_power: f32,
+    /// Hottest GPU temperature in Celsius, 0 without GPUs
+    pub max_temperature: u32,
+    /// Processes summed over all GPUs
+    pub process_count: usize,
+}
+
+impl AggregateStats {
+    /// Aggregate a sample of GPUs
+    pub fn from_gpus(gpus: &[GpuInfo]) -> Self {
+        let utilization: u32 = gpus.iter().map(|g| g.metrics.gpu_utilization).sum();
+        Self {
+            gpu_count: gpus.len(),
+            avg_utilization: if gpus.is_empty() { 0.0 } else { utilization as f32 / gpus.len() as f32 },
+            total_memory_used: gpus.iter().map(|g| g.memory.used).sum(),
+            total_memory: gpus.iter().map(|g| g.memory.total).sum(),
+            total_power: gpus.iter().map(|g| g.metrics.power_watts()).sum(),
+            max_temperature: gpus.iter().map(|g| g.metrics.temperature).max().unwrap_or(0),
+            process_count: gpus.iter().map(|g| g.process_count).sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty() {
+        let stats = AggregateStats::from_gpus(&[]);
+        assert_eq!(stats.gpu_count, 0);
+        assert_eq!(stats.avg_utilization, 0.0);
+        assert_eq!(stats.max_temperature, 0);
+    }
+
+    #[test]
+    fn test_two_gpus() {
+        let gpu = |utilization, temperature, power_usage, used, process_count| {
+            let mut gpu = GpuInfo::default();
+            gpu.metrics.gpu_utilization = utilization;
+            gpu.metrics.temperature = temperature;
+            gpu.metrics.power_usage = power_usage;
+            gpu.memory.total = 8_000;
+            gpu.memory.used = used;
+            gpu.process_count = process_count;
+            gpu
+        };
+        let stats = AggregateStats::from_gpus(&[gpu(90, 75, 250_000, 6_000, 3), gpu(15, 48, 80_500, 1_000, 1)]);
+
+        assert_eq!(stats.gpu_count, 2);
+        assert_eq!(stats.avg_utilization, 52.5);
+        assert_eq!(stats.total_memory_used, 7_000);
+        assert_eq!(stats.total_memory, 16_000);
+        assert_eq!(stats.total_power, 330.5);
+        assert_eq!(stats.max_temperature, 75);
+        assert_eq!(stats.process_count, 4);
+    }
+}
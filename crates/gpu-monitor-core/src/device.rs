@@ -1,71 +1,363 @@
-//! GPU device information types
-
-use serde::{Deserialize, Serialize};
-
-/// Static information about a GPU device
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DeviceInfo {
-    /// Device index (0-based)
-    pub index: u32,
-    /// Device name (e.g., "NVIDIA GeForce RTX 4060 Ti")
-    pub name: String,
-    /// Unique device identifier
-    pub uuid: String,
-    /// PCI bus ID
-    pub pci_bus_id: String,
-    /// Driver version
-    pub driver_version: String,
-    /// CUDA version (if available)
-    pub cuda_version: Option<String>,
-    /// Power limit in watts
-    pub power_limit: u32,
-    /// Maximum power limit in watts
-    pub power_limit_max: u32,
-}
-
-/// GPU memory information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MemoryInfo {
-    /// Total memory in bytes
-    pub total: u64,
-    /// Used memory in bytes
-    pub used: u64,
-    /// Free memory in bytes
-    pub free: u64,
-}
-
-impl MemoryInfo {
-    /// Get memory usage as percentage (0-100)
-    pub fn usage_percent(&self) -> f32 {
-        if self.total == 0 {
-            0.0
-        } else {
-            (self.used as f32 / self.total as f32) * 100.0
-        }
-    }
-
-    /// Get total memory in MiB
-    pub fn total_mib(&self) -> u64 {
-        self.total / (1024 * 1024)
-    }
-
-    /// Get used memory in MiB
-    pub fn used_mib(&self) -> u64 {
-        self.used / (1024 * 1024)
-    }
-
-    /// Get free memory in MiB
-    pub fn free_mib(&self) -> u64 {
-        self.free / (1024 * 1024)
-    }
-
-    /// Get total memory in GiB
-    pub fn total_gib(&self) -> f32 {
-        self.total as f32 / (1024.0 * 1024.0 * 1024.0)
-    }
-
-    /// Get used memory in GiB
-    pub fn used_gib(&self) -> f32 {
-        self.used as f32 / (1024.0 * 1024.0 * 1024.0)
-    }
-}
+//! GPU device information types
+
+use serde::{Deserialize, Serialize};
+
+/// Static information about a GPU device
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    /// Device index (0-based)
+    pub index: u32,
+    /// Device name (e.g., "NVIDIA GeForce RTX 4060 Ti")
+    pub name: String,
+    /// Unique device identifier
+    pub uuid: String,
+    /// PCI bus ID
+    pub pci_bus_id: String,
+    /// Device minor number (N in /dev/nvidiaN), None if not supported
+    pub minor_number: Option<u32>,
+    /// Board ID shared by devices on the same physical board, None if not supported
+    pub board_id: Option<u32>,
+    /// NUMA node the GPU is attached to, None if unknown or the system is not NUMA
+    pub numa_node: Option<i32>,
+    /// Driver version
+    pub driver_version: String,
+    /// Video BIOS version, None if not supported
+    pub vbios_version: Option<String>,
+    /// CUDA version (if available)
+    pub cuda_version: Option<String>,
+    /// Power limit in watts
+    pub power_limit: u32,
+    /// Maximum power limit in watts
+    pub power_limit_max: u32,
+    /// Temperature in Celsius at which hardware slowdown begins, None if not supported
+    pub temp_slowdown: Option<u32>,
+    /// Temperature in Celsius at which the GPU shuts down, None if not supported
+    pub temp_shutdown: Option<u32>,
+    /// Memory temperature in Celsius at which software slowdown begins, None if not supported
+    pub temp_memory_max: Option<u32>,
+    /// Device has a video encoder (NVENC) engine
+    pub has_encoder: bool,
+    /// Device has a video decoder (NVDEC) engine
+    pub has_decoder: bool,
+    /// Device has its own fans; fan speed is meaningless on passively cooled cards
+    pub has_fan: bool,
+    /// MIG mode is currently enabled (false if not supported)
+    pub mig_enabled: bool,
+    /// MIG mode will be enabled after the next GPU reset
+    pub mig_pending: bool,
+    /// ECC is currently enabled, None if ECC is not supported
+    pub ecc_enabled_current: Option<bool>,
+    /// ECC will be enabled after the next reboot, None if ECC is not supported
+    pub ecc_enabled_pending: Option<bool>,
+    /// Fan control policy, None if not supported
+    pub fan_control_policy: Option<FanPolicy>,
+    /// Supported clock range, None if not supported
+    pub clock_range: Option<ClockRange>,
+    /// Current application graphics clock in MHz
+    pub app_clock_graphics: Option<u32>,
+    /// Current application memory clock in MHz
+    pub app_clock_memory: Option<u32>,
+    /// Default application graphics clock in MHz
+    pub default_app_clock_graphics: Option<u32>,
+    /// Default application memory clock in MHz
+    pub default_app_clock_memory: Option<u32>,
+    /// Whether and how the graphics clock is pinned
+    pub clock_state: ClockState,
+    /// Base graphics clock in MHz, None if not supported
+    pub clock_graphics_base: Option<u32>,
+    /// Base memory clock in MHz, None if not supported
+    pub clock_memory_base: Option<u32>,
+    /// Rated boost graphics clock in MHz set by the vendor (VBIOS), None if not supported
+    ///
+    /// The clock the card is specified to reach, unlike `clock_range`'s
+    /// maximum which is the highest clock the GPU allows.
+    pub boost_clock_rated: Option<u32>,
+    /// Persistence mode keeps the driver loaded with no clients, None if not supported (Linux only)
+    pub persistence_mode: Option<bool>,
+    /// Driver model (TCC/WDDM), None on Linux or if not supported
+    pub driver_model: Option<DriverModel>,
+}
+
+impl DeviceInfo {
+    /// Get the application graphics clock if it was changed from the default
+    pub fn locked_graphics_clock(&self) -> Option<u32> {
+        match (self.app_clock_graphics, self.default_app_clock_graphics) {
+            (Some(current), Some(default)) if current != default => Some(current),
+            _ => None,
+        }
+    }
+
+    /// Check if an ECC mode change is waiting for a reboot
+    pub fn ecc_change_pending(&self) -> bool {
+        matches!(
+            (self.ecc_enabled_current, self.ecc_enabled_pending),
+            (Some(current), Some(pending)) if current != pending
+        )
+    }
+
+    /// Get warnings about metrics known to be unreliable on this board and VBIOS
+    pub fn known_issues(&self) -> Vec<String> {
+        let vbios = self.vbios_version.as_deref().unwrap_or_default();
+        KNOWN_ISSUES
+            .iter()
+            .filter(|issue| issue.matches(&self.name, vbios))
+            .map(|issue| issue.warning.to_string())
+            .collect()
+    }
+}
+
+/// Metric that is unreliable on some boards or VBIOS releases
+struct KnownIssue {
+    /// Substring of the device name
+    name: &'static str,
+    /// VBIOS version prefix, empty to match any version
+    vbios_prefix: &'static str,
+    /// Warning shown to the user
+    warning: &'static str,
+}
+
+impl KnownIssue {
+    /// Check if the issue applies to a device name and VBIOS version
+    fn matches(&self, name: &str, vbios: &str) -> bool {
+        name.contains(self.name) && vbios.starts_with(self.vbios_prefix)
+    }
+}
+
+/// Known issues, matched against the device name and VBIOS version
+///
+/// Each entry needs a source (NVIDIA release notes or errata) cited next to it.
+const KNOWN_ISSUES: &[KnownIssue] = &[];
+
+/// Fan control policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FanPolicy {
+    /// Fan speed is controlled automatically by the driver
+    Auto,
+    /// Fan speed is set manually
+    Manual,
+}
+
+/// Optional features supported by a GPU device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// ECC memory error correction is available
+    pub has_ecc: bool,
+    /// At least one NVLink is available
+    pub has_nvlink: bool,
+    /// MIG partitioning is supported
+    pub has_mig: bool,
+    /// Power limit can be queried and adjusted within a range (never while MIG is enabled)
+    pub has_power_limit_control: bool,
+    /// Fan speed is reported and the fan control policy can be queried (never while MIG is enabled)
+    pub has_fan_control: bool,
+    /// Application clocks can be set (never while MIG is enabled)
+    pub has_clock_control: bool,
+    /// Video encoder (NVENC) engine is present
+    pub has_encoder: bool,
+    /// Video decoder (NVDEC) engine is present
+    pub has_decoder: bool,
+    /// Remapped memory rows are reported
+    pub has_remapped_rows: bool,
+    /// Persistence mode is reported and can be set (Linux only)
+    pub has_persistence_control: bool,
+    /// The GPU can be reset through nvidia-smi (not under the Windows WDDM driver model)
+    pub has_reset: bool,
+}
+
+/// Windows driver model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DriverModel {
+    /// GPU is treated as a compute device (required for most compute workloads)
+    Tcc,
+    /// GPU is treated as a display device
+    Wddm,
+}
+
+impl DriverModel {
+    /// Get the short display label
+    pub fn label(&self) -> &'static str {
+        match self {
+            DriverModel::Tcc => "TCC",
+            DriverModel::Wddm => "WDDM",
+        }
+    }
+}
+
+/// How the graphics clock is controlled
+///
+/// Derived from the application clocks. NVML has no query for a locked
+/// clock range (`nvidia-smi -lgc`), so such a lock is not detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClockState {
+    /// Clocks follow the driver's boost behavior
+    #[default]
+    Auto,
+    /// Application clocks were changed from their defaults
+    /// (`nvidia-smi -ac`): the current application graphics clock differs
+    /// from the default one
+    ApplicationLocked,
+    /// Application clocks are not supported, so the state cannot be told
+    Unknown,
+}
+
+impl ClockState {
+    /// Derive the clock state from the current and default application graphics clocks
+    pub fn infer(app_clock_graphics: Option<u32>, default_app_clock_graphics: Option<u32>) -> Self {
+        match (app_clock_graphics, default_app_clock_graphics) {
+            (Some(current), Some(default)) if current != default => Self::ApplicationLocked,
+            (Some(_), Some(_)) => Self::Auto,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Supported clock range of a GPU device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockRange {
+    /// Lowest supported graphics clock in MHz
+    pub graphics_min: u32,
+    /// Highest supported graphics clock in MHz
+    pub graphics_max: u32,
+    /// Lowest supported memory clock in MHz
+    pub memory_min: u32,
+    /// Highest supported memory clock in MHz
+    pub memory_max: u32,
+}
+
+/// GPU memory information
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryInfo {
+    /// Total memory in bytes
+    pub total: u64,
+    /// Used memory in bytes
+    pub used: u64,
+    /// Free memory in bytes
+    pub free: u64,
+    /// Memory reserved by the driver in bytes, None if not supported
+    pub reserved: Option<u64>,
+    /// Memory taken by ECC check bits in bytes, None if ECC is off or not supported
+    ///
+    /// GDDR boards keep check bits in regular memory (1/16 of it), so `total`
+    /// of a 16 GB card drops to 15 GB with ECC on; HBM has dedicated ECC
+    /// storage and loses nothing. NVML does not report this, so it is
+    /// estimated from the memory bus width.
+    pub ecc_reserved: Option<u64>,
+    /// Total BAR1 (CPU-mappable) memory in bytes, None if not supported
+    pub bar1_total: Option<u64>,
+    /// Used BAR1 memory in bytes, None if not supported
+    pub bar1_used: Option<u64>,
+}
+
+impl MemoryInfo {
+    /// Get memory usage as percentage (0-100) of total capacity
+    ///
+    /// This is how full memory is, not bandwidth load; see
+    /// `GpuMetrics::memory_controller_load` for the latter.
+    pub fn usage_percent(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.used as f32 / self.total as f32) * 100.0
+        }
+    }
+
+    /// Get the physical memory size in bytes, including memory taken by ECC
+    pub fn physical_total(&self) -> u64 {
+        self.total + self.ecc_reserved.unwrap_or(0)
+    }
+
+    /// Get total memory in MiB
+    pub fn total_mib(&self) -> u64 {
+        self.total / (1024 * 1024)
+    }
+
+    /// Get used memory in MiB
+    pub fn used_mib(&self) -> u64 {
+        self.used / (1024 * 1024)
+    }
+
+    /// Get free memory in MiB
+    pub fn free_mib(&self) -> u64 {
+        self.free / (1024 * 1024)
+    }
+
+    /// Get total memory in GiB
+    pub fn total_gib(&self) -> f32 {
+        self.total as f32 / (1024.0 * 1024.0 * 1024.0)
+    }
+
+    /// Get used memory in GiB
+    pub fn used_gib(&self) -> f32 {
+        self.used as f32 / (1024.0 * 1024.0 * 1024.0)
+    }
+
+    /// Get total memory in decimal GB
+    pub fn total_gb(&self) -> f32 {
+        self.total as f32 / 1_000_000_000.0
+    }
+
+    /// Get used memory in decimal GB
+    pub fn used_gb(&self) -> f32 {
+        self.used as f32 / 1_000_000_000.0
+    }
+}
+
+/// A MIG instance: one compute instance inside a GPU instance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigInstance {
+    /// GPU instance ID
+    pub gpu_instance_id: u32,
+    /// Compute instance ID within the GPU instance
+    pub compute_instance_id: u32,
+    /// GPU instance profile, e.g. "1g.5gb" or "3g.20gb" ("profile <id>" if unknown)
+    pub profile: String,
+    /// MIG device UUID (`MIG-...`)
+    pub uuid: String,
+    /// Memory available to the instance in bytes
+    pub memory_total: u64,
+}
+
+/// Remapped memory rows (Ampere and later)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemappedRows {
+    /// Rows remapped due to correctable errors
+    pub correctable: u32,
+    /// Rows remapped due to uncorrectable errors
+    pub uncorrectable: u32,
+    /// A remapping is pending and takes effect after the next GPU reset
+    pub pending: bool,
+    /// A remapping failed in the past
+    pub failure_occurred: bool,
+}
+
+/// Retired memory pages (pre-Ampere ECC devices)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetiredPages {
+    /// Pages retired due to multiple single bit ECC errors
+    pub single_bit_ecc: u32,
+    /// Pages retired due to double bit ECC errors
+    pub double_bit_ecc: u32,
+    /// Pages are pending retirement and are retired after the next GPU reset
+    pub pending: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_issue_matches() {
+        let issue = KnownIssue {
+            name: "Example GPU",
+            vbios_prefix: "90.00.",
+            warning: "power_usage unreliable",
+        };
+        assert!(issue.matches("NVIDIA Example GPU", "90.00.12.00.01"));
+        assert!(!issue.matches("NVIDIA Example GPU", "90.01.00.00.01"));
+        assert!(!issue.matches("NVIDIA Other GPU", "90.00.12.00.01"));
+
+        // An empty prefix matches every VBIOS, including an unknown one
+        let any = KnownIssue { vbios_prefix: "", ..issue };
+        assert!(any.matches("NVIDIA Example GPU", ""));
+    }
+}
@@ -1,38 +1,96 @@
-//! Error types for GPU monitoring operations
-
-use thiserror::Error;
-
-/// Result type alias using our Error type
-pub type Result<T> = std::result::Result<T, Error>;
-
-/// Errors that can occur during GPU monitoring
-#[derive(Error, Debug)]
-pub enum Error {
-    /// NVML library initialization failed
-    #[error("Failed to initialize NVML: {0}")]
-    NvmlInit(String),
-
-    /// NVML operation failed
-    #[error("NVML error: {0}")]
-    Nvml(#[from] nvml_wrapper::error::NvmlError),
-
-    /// No GPU devices found
-    #[error("No NVIDIA GPU devices found")]
-    NoDevices,
-
-    /// Invalid device index
-    #[error("Invalid GPU device index: {0}")]
-    InvalidDevice(u32),
-
-    /// Failed to get process information
-    #[error("Failed to get process info: {0}")]
-    ProcessInfo(String),
-
-    /// IO error
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
-
-    /// Serialization error
-    #[error("Serialization error: {0}")]
-    Serialization(#[from] serde_json::Error),
-}
+//! Error types for GPU monitoring operations
+
+use thiserror::Error;
+
+/// Result type alias using our Error type
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors that can occur during GPU monitoring
+#[derive(Error, Debug)]
+pub enum Error {
+    /// NVML library initialization failed
+    #[error("Failed to initialize NVML: {0}")]
+    NvmlInit(String),
+
+    /// NVML operation failed
+    #[error("NVML error: {0}")]
+    Nvml(#[from] nvml_wrapper::error::NvmlError),
+
+    /// NVML operation on a specific GPU failed
+    #[error("GPU {index}: NVML error: {source}")]
+    Device {
+        index: u32,
+        source: nvml_wrapper::error::NvmlError,
+    },
+
+    /// No GPU devices found
+    #[error("No NVIDIA GPU devices found")]
+    NoDevices,
+
+    /// Invalid device index
+    #[error("Invalid GPU device index: {0}")]
+    InvalidDevice(u32),
+
+    /// CUDA device ordinal does not map to a visible device
+    #[error("CUDA device {0} is not visible (check CUDA_VISIBLE_DEVICES)")]
+    InvalidCudaDevice(u32),
+
+    /// Failed to get process information
+    #[error("Failed to get process info: {0}")]
+    ProcessInfo(String),
+
+    /// Not permitted to signal the process
+    #[error("Permission denied to kill process {0}")]
+    PermissionDenied(u32),
+
+    /// Process does not exist or is not using a GPU
+    #[error("No GPU process with PID {0}")]
+    ProcessNotFound(u32),
+
+    /// GPU has running processes or an active display
+    #[error("GPU {0} is in use; stop its processes or use force")]
+    GpuBusy(u32),
+
+    /// Not permitted to reset the GPU (requires root)
+    #[error("Permission denied to reset GPU {0}")]
+    ResetDenied(u32),
+
+    /// Not permitted to change persistence mode (requires root)
+    #[error("Permission denied to change persistence mode of GPU {0}")]
+    PersistenceDenied(u32),
+
+    /// Accounting mode is off, so the driver records no per-process statistics
+    #[error("Accounting mode is disabled on GPU {0} (enable it with `nvidia-smi -am 1`)")]
+    AccountingDisabled(u32),
+
+    /// GPU reset failed
+    #[error("Failed to reset GPU {index}: {reason}")]
+    ResetFailed { index: u32, reason: String },
+
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Serialization error
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl Error {
+    /// Check if the error is transient and the operation may succeed on retry
+    pub fn is_transient(&self) -> bool {
+        use nvml_wrapper::error::NvmlError;
+        match self {
+            Self::Nvml(e) | Self::Device { source: e, .. } => matches!(e, NvmlError::Unknown | NvmlError::Timeout),
+            _ => false,
+        }
+    }
+
+    /// Attach a device index to an NVML error, leaving other errors unchanged
+    pub(crate) fn on_device(self, index: u32) -> Self {
+        match self {
+            Self::Nvml(source) => Self::Device { index, source },
+            e => e,
+        }
+    }
+}
@@ -1,44 +1,227 @@
-//! GPU Monitor Core Library
-//!
-//! Provides GPU monitoring functionality through NVIDIA Management Library (NVML).
-//!
-//! # Features
-//! - GPU device information
-//! - Real-time metrics (usage, memory, temperature, power)
-//! - Process monitoring
-//!
-//! # Example
-//! ```no_run
-//! use gpu_monitor_core::GpuMonitor;
-//!
-//! let monitor = GpuMonitor::new()?;
-//! let gpus = monitor.get_all_gpu_info()?;
-//! for gpu in gpus {
-//!     println!("{}: {}% usage", gpu.name, gpu.metrics.gpu_utilization);
-//! }
-//! ```
-
-mod device;
-mod error;
-pub mod metrics;
-mod monitor;
-mod process;
-
-pub use device::{DeviceInfo, MemoryInfo};
-pub use error::{Error, Result};
-pub use metrics::GpuMetrics;
-pub use monitor::GpuMonitor;
-pub use process::GpuProcess;
-
-/// Complete GPU information including device info, metrics, and processes
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct GpuInfo {
-    /// Device information (name, UUID, etc.)
-    pub device: DeviceInfo,
-    /// Current metrics (usage, temperature, etc.)
-    pub metrics: GpuMetrics,
-    /// Memory information
-    pub memory: MemoryInfo,
-    /// Processes using this GPU
-    pub processes: Vec<GpuProcess>,
-}
+//! GPU Monitor Core Library
+//!
+//! Provides GPU monitoring functionality through NVIDIA Management Library (NVML).
+//!
+//! # Features
+//! - GPU device information
+//! - Real-time metrics (usage, memory, temperature, power)
+//! - Process monitoring
+//! - XID error events from the kernel log (`xid` feature, Linux only)
+//!
+//! # Example
+//! ```no_run
+//! use gpu_monitor_core::GpuMonitor;
+//!
+//! let monitor = GpuMonitor::new()?;
+//! let gpus = monitor.get_all_gpu_info()?;
+//! for gpu in gpus {
+//!     println!("{}: {}% usage", gpu.device.name, gpu.metrics.gpu_utilization);
+//! }
+//! # Ok::<(), gpu_monitor_core::Error>(())
+//! ```
+
+mod aggregate;
+mod device;
+mod error;
+pub mod metrics;
+mod monitor;
+mod process;
+mod raw;
+mod stream;
+mod topology;
+#[cfg(all(feature = "xid", target_os = "linux"))]
+mod xid;
+
+pub use aggregate::AggregateStats;
+pub use device::{Capabilities, ClockRange, ClockState, DeviceInfo, DriverModel, FanPolicy, MemoryInfo, MigInstance, RemappedRows, RetiredPages};
+pub use error::{Error, Result};
+pub use metrics::{GpuMetrics, MetricSample, ThrottleReason};
+pub use monitor::{GpuMonitor, GpuMonitorBuilder, Options};
+pub use process::{AccountingRecord, GpuProcess, ProcessMemoryTree, ProcessType, UserMemory, UNKNOWN_OTHER_NAMESPACE};
+pub use stream::{Samples, Snapshot};
+pub use topology::{CpuRange, LinkType, Topology};
+#[cfg(all(feature = "xid", target_os = "linux"))]
+pub use xid::XidEvent;
+
+/// Degrees Celsius from the shutdown temperature at which a GPU stops counting as healthy
+pub const SHUTDOWN_MARGIN: i32 = 5;
+
+/// Complete GPU information including device info, metrics, and processes
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct GpuInfo {
+    /// Device information (name, UUID, etc.)
+    pub device: DeviceInfo,
+    /// Current metrics (usage, temperature, etc.)
+    pub metrics: GpuMetrics,
+    /// Memory information
+    pub memory: MemoryInfo,
+    /// Remapped memory rows, None if not supported
+    pub remapped_rows: Option<RemappedRows>,
+    /// Retired memory pages, None if not supported
+    pub retired_pages: Option<RetiredPages>,
+    /// Active clock throttle reasons
+    pub throttle_reasons: Vec<ThrottleReason>,
+    /// Caveats about metrics that are unreliable on this board or VBIOS
+    pub warnings: Vec<String>,
+    /// MIG instances of this GPU, empty if MIG is disabled
+    pub mig_instances: Vec<MigInstance>,
+    /// GPU drives a display: NVML reports an active display or an X server/Wayland compositor runs on it (best effort)
+    pub driving_display: bool,
+    /// Processes using this GPU (highest memory first, possibly capped)
+    pub processes: Vec<GpuProcess>,
+    /// Total number of processes using this GPU, including any beyond the cap
+    pub process_count: usize,
+    /// Processes were enumerated; false when skipped with `Options { processes: false }`
+    pub processes_collected: bool,
+    /// Sample timestamp in milliseconds since the Unix epoch
+    ///
+    /// All GPUs returned by one `get_all_gpu_info` call share the same timestamp.
+    pub sampled_at: u64,
+    /// Recent XID errors reported by the kernel driver for this GPU
+    #[cfg(all(feature = "xid", target_os = "linux"))]
+    pub recent_xids: Vec<XidEvent>,
+}
+
+impl GpuInfo {
+    /// Get encoder utilization, None if the device has no encoder
+    pub fn encoder_utilization(&self) -> Option<u32> {
+        self.device
+            .has_encoder
+            .then_some(self.metrics.encoder_utilization)
+    }
+
+    /// Check if a memory row remapping or page retirement waits for a GPU reset
+    pub fn memory_retirement_pending(&self) -> bool {
+        self.remapped_rows.is_some_and(|r| r.pending)
+            || self.retired_pages.is_some_and(|p| p.pending)
+    }
+
+    /// Used memory not attributed to any process: driver, CUDA contexts and reservations
+    ///
+    /// `memory.used` minus the memory of all processes, clamped at zero. None if
+    /// processes were not collected or the list is capped, since the process sum
+    /// would be missing or partial.
+    pub fn memory_overhead(&self) -> Option<u64> {
+        if !self.processes_collected || self.processes.len() < self.process_count {
+            return None;
+        }
+        let process_memory: u64 = self.processes.iter().map(|p| p.gpu_memory).sum();
+        Some(self.memory.used.saturating_sub(process_memory))
+    }
+
+    /// Check if nothing needs attention, as one status for simple dashboards
+    ///
+    /// A GPU is unhealthy while it is throttled for heat or by the hardware
+    /// (the power cap is normal under load and does not count), within
+    /// [`SHUTDOWN_MARGIN`] of its shutdown temperature, has uncorrectable ECC
+    /// errors or a failed row remapping, or has memory retirement pending.
+    /// Checks whose data the device does not report are skipped.
+    pub fn is_healthy(&self) -> bool {
+        let throttled = self.throttle_reasons.iter().any(|r| {
+            matches!(
+                r,
+                ThrottleReason::HwSlowdown
+                    | ThrottleReason::SwThermalSlowdown
+                    | ThrottleReason::HwThermalSlowdown
+                    | ThrottleReason::HwPowerBrakeSlowdown
+                    | ThrottleReason::MemoryThermalSlowdown
+            )
+        });
+        let near_shutdown = self
+            .device
+            .temp_shutdown
+            .is_some_and(|shutdown| self.metrics.thermal_margin(shutdown) <= SHUTDOWN_MARGIN);
+        let ecc_errors = self.remapped_rows.is_some_and(|r| r.uncorrectable > 0 || r.failure_occurred)
+            || self.retired_pages.is_some_and(|p| p.double_bit_ecc > 0);
+        !(throttled || near_shutdown || ecc_errors || self.memory_retirement_pending())
+    }
+
+    /// Degrees Celsius left before hardware slowdown, None if the threshold is unknown
+    pub fn thermal_margin(&self) -> Option<i32> {
+        Some(self.metrics.thermal_margin(self.device.temp_slowdown?))
+    }
+
+    /// Explain why graphics clocks are below the maximum, None if not throttled
+    pub fn throttle_diagnostic(&self) -> Option<String> {
+        let max_clock = self.device.clock_range?.graphics_max;
+        self.metrics.throttle_diagnostic(max_clock, &self.throttle_reasons)
+    }
+
+    /// Check if clocks are held down by an external power brake signal
+    ///
+    /// Unlike the power cap this is asserted by the system (PSU or VRM), so it
+    /// points at an underpowered host rather than at the card's own limit.
+    pub fn power_brake_active(&self) -> bool {
+        self.throttle_reasons
+            .contains(&ThrottleReason::HwPowerBrakeSlowdown)
+    }
+
+    /// Get decoder utilization, None if the device has no decoder
+    pub fn decoder_utilization(&self) -> Option<u32> {
+        self.device
+            .has_decoder
+            .then_some(self.metrics.decoder_utilization)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_healthy() {
+        let mut healthy = GpuInfo::default();
+        healthy.metrics.temperature = 60;
+        healthy.device.temp_shutdown = Some(90);
+        healthy.throttle_reasons = vec![ThrottleReason::SwPowerCap];
+        healthy.remapped_rows = Some(RemappedRows {
+            correctable: 2,
+            uncorrectable: 0,
+            pending: false,
+            failure_occurred: false,
+        });
+        healthy.retired_pages = Some(RetiredPages { single_bit_ecc: 1, double_bit_ecc: 0, pending: false });
+        assert!(healthy.is_healthy());
+
+        for reason in [
+            ThrottleReason::HwSlowdown,
+            ThrottleReason::SwThermalSlowdown,
+            ThrottleReason::HwThermalSlowdown,
+            ThrottleReason::HwPowerBrakeSlowdown,
+            ThrottleReason::MemoryThermalSlowdown,
+        ] {
+            let mut gpu = healthy.clone();
+            gpu.throttle_reasons.push(reason);
+            assert!(!gpu.is_healthy(), "{:?}", reason);
+        }
+
+        let mut gpu = healthy.clone();
+        gpu.metrics.temperature = 90 - SHUTDOWN_MARGIN as u32;
+        assert!(!gpu.is_healthy());
+
+        let unhealthy_rows = [
+            RemappedRows { correctable: 0, uncorrectable: 1, pending: false, failure_occurred: false },
+            RemappedRows { correctable: 0, uncorrectable: 0, pending: false, failure_occurred: true },
+            RemappedRows { correctable: 0, uncorrectable: 0, pending: true, failure_occurred: false },
+        ];
+        for rows in unhealthy_rows {
+            let mut gpu = healthy.clone();
+            gpu.remapped_rows = Some(rows);
+            assert!(!gpu.is_healthy(), "{:?}", rows);
+        }
+
+        let unhealthy_pages = [
+            RetiredPages { single_bit_ecc: 0, double_bit_ecc: 1, pending: false },
+            RetiredPages { single_bit_ecc: 0, double_bit_ecc: 0, pending: true },
+        ];
+        for pages in unhealthy_pages {
+            let mut gpu = healthy.clone();
+            gpu.retired_pages = Some(pages);
+            assert!(!gpu.is_healthy(), "{:?}", pages);
+        }
+
+        // Checks without data are skipped
+        let unknown = GpuInfo::default();
+        assert!(unknown.is_healthy());
+    }
+}
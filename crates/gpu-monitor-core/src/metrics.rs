@@ -1,80 +1,289 @@
-//! GPU real-time metrics
-
-use serde::{Deserialize, Serialize};
-
-/// Real-time GPU metrics
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GpuMetrics {
-    /// GPU utilization percentage (0-100)
-    pub gpu_utilization: u32,
-    /// Memory utilization percentage (0-100)
-    pub memory_utilization: u32,
-    /// Encoder utilization percentage (0-100)
-    pub encoder_utilization: u32,
-    /// Decoder utilization percentage (0-100)
-    pub decoder_utilization: u32,
-    /// Current temperature in Celsius
-    pub temperature: u32,
-    /// Current power usage in milliwatts
-    pub power_usage: u32,
-    /// Fan speed percentage (0-100), None if not available
-    pub fan_speed: Option<u32>,
-    /// Current graphics clock in MHz
-    pub clock_graphics: u32,
-    /// Current memory clock in MHz
-    pub clock_memory: u32,
-    /// Current SM clock in MHz
-    pub clock_sm: u32,
-}
-
-impl GpuMetrics {
-    /// Get power usage in watts
-    pub fn power_watts(&self) -> f32 {
-        self.power_usage as f32 / 1000.0
-    }
-
-    /// Check if GPU is idle (less than 5% utilization)
-    pub fn is_idle(&self) -> bool {
-        self.gpu_utilization < 5
-    }
-
-    /// Check if GPU is under heavy load (more than 80% utilization)
-    pub fn is_heavy_load(&self) -> bool {
-        self.gpu_utilization > 80
-    }
-
-    /// Get temperature status
-    pub fn temperature_status(&self) -> TemperatureStatus {
-        match self.temperature {
-            0..=50 => TemperatureStatus::Cool,
-            51..=70 => TemperatureStatus::Normal,
-            71..=85 => TemperatureStatus::Warm,
-            _ => TemperatureStatus::Hot,
-        }
-    }
-}
-
-/// Temperature status categories
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum TemperatureStatus {
-    /// Below 50°C
-    Cool,
-    /// 51-70°C
-    Normal,
-    /// 71-85°C
-    Warm,
-    /// Above 85°C
-    Hot,
-}
-
-impl TemperatureStatus {
-    /// Get color hint for UI (CSS color name)
-    pub fn color(&self) -> &'static str {
-        match self {
-            Self::Cool => "green",
-            Self::Normal => "blue",
-            Self::Warm => "orange",
-            Self::Hot => "red",
-        }
-    }
-}
+//! GPU real-time metrics
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Utilization percentage below which an engine counts as idle
+const IDLE_THRESHOLD: u32 = 5;
+
+/// Real-time GPU metrics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GpuMetrics {
+    /// GPU utilization percentage (0-100)
+    pub gpu_utilization: u32,
+    /// Memory controller utilization percentage (0-100)
+    ///
+    /// Percent of time memory was being read or written during the last
+    /// sample period, not how much memory is allocated. See
+    /// [`MemoryInfo::usage_percent`](crate::MemoryInfo::usage_percent) for capacity.
+    pub memory_utilization: u32,
+    /// Encoder utilization percentage (0-100)
+    pub encoder_utilization: u32,
+    /// Decoder utilization percentage (0-100)
+    pub decoder_utilization: u32,
+    /// Period in microseconds over which encoder utilization was measured
+    pub encoder_sampling_us: Option<u32>,
+    /// Period in microseconds over which decoder utilization was measured
+    pub decoder_sampling_us: Option<u32>,
+    /// Optical flow accelerator utilization percentage, None if the device has no OFA
+    pub ofa_utilization: Option<u32>,
+    /// JPEG decoder utilization percentage, None if the device has no JPEG engine
+    pub jpeg_utilization: Option<u32>,
+    /// Current temperature in Celsius
+    pub temperature: u32,
+    /// Current power usage in milliwatts
+    pub power_usage: u32,
+    /// Speed of the first fan as a percentage (0-100), None if not available
+    pub fan_speed: Option<u32>,
+    /// Speed of every fan as a percentage (0-100), empty if not available
+    pub fan_speeds: Vec<u32>,
+    /// Current graphics clock in MHz
+    pub clock_graphics: u32,
+    /// Current memory clock in MHz
+    pub clock_memory: u32,
+    /// Current SM clock in MHz
+    pub clock_sm: u32,
+    /// PCIe link-level replays since the driver loaded, None if not supported
+    ///
+    /// A steadily rising count points at a marginal riser, slot or cable.
+    pub pcie_replay_count: Option<u64>,
+    /// Temperature in Celsius of every sensor the device reports, keyed by
+    /// sensor: "gpu" (same as `temperature`), "memory", "board", ...
+    ///
+    /// On some cards memory runs hotter than the GPU and is what limits clocks.
+    pub temperatures: BTreeMap<String, u32>,
+}
+
+impl GpuMetrics {
+    /// Get power usage in watts
+    pub fn power_watts(&self) -> f32 {
+        self.power_usage as f32 / 1000.0
+    }
+
+    /// Get memory controller load (0-100): how busy memory bandwidth was
+    ///
+    /// This is `memory_utilization` under a clearer name. It is unrelated to
+    /// how full memory is, which is `MemoryInfo::usage_percent`.
+    pub fn memory_controller_load(&self) -> u32 {
+        self.memory_utilization
+    }
+
+    /// Check if GPU is idle: graphics, encoder and decoder all below 5% utilization
+    ///
+    /// A transcoding GPU keeps the graphics engine nearly idle while NVENC or
+    /// NVDEC is busy, so graphics utilization alone would call it idle.
+    pub fn is_idle(&self) -> bool {
+        [self.gpu_utilization, self.encoder_utilization, self.decoder_utilization]
+            .iter()
+            .all(|&u| u < IDLE_THRESHOLD)
+    }
+
+    /// Check if every engine is idle, see [`is_idle`](Self::is_idle)
+    ///
+    /// Also requires the OFA and JPEG engines (where present) and the memory
+    /// controller, which copy engine transfers keep busy, to be below 5%.
+    pub fn is_fully_idle(&self) -> bool {
+        self.is_idle()
+            && self.memory_utilization < IDLE_THRESHOLD
+            && self.ofa_utilization.is_none_or(|u| u < IDLE_THRESHOLD)
+            && self.jpeg_utilization.is_none_or(|u| u < IDLE_THRESHOLD)
+    }
+
+    /// Check if GPU is under heavy load (more than 80% utilization)
+    pub fn is_heavy_load(&self) -> bool {
+        self.gpu_utilization > 80
+    }
+
+    /// Explain reduced graphics clocks, e.g. "clocks reduced by 450 MHz due to power cap"
+    ///
+    /// Returns None when the clock is at `max_clock` or no throttle reason
+    /// other than idle is active.
+    pub fn throttle_diagnostic(&self, max_clock: u32, reasons: &[ThrottleReason]) -> Option<String> {
+        let deficit = max_clock.checked_sub(self.clock_graphics).filter(|&d| d > 0)?;
+        let causes: Vec<&str> = reasons
+            .iter()
+            .filter(|r| **r != ThrottleReason::GpuIdle)
+            .map(|r| r.label())
+            .collect();
+        if causes.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "clocks reduced by {} MHz due to {}",
+            deficit,
+            causes.join(", ")
+        ))
+    }
+
+    /// Degrees Celsius left before the given slowdown threshold (negative once past it)
+    pub fn thermal_margin(&self, slowdown: u32) -> i32 {
+        slowdown as i32 - self.temperature as i32
+    }
+
+    /// Get temperature status
+    pub fn temperature_status(&self) -> TemperatureStatus {
+        match self.temperature {
+            0..=50 => TemperatureStatus::Cool,
+            51..=70 => TemperatureStatus::Normal,
+            71..=85 => TemperatureStatus::Warm,
+            _ => TemperatureStatus::Hot,
+        }
+    }
+}
+
+/// Timestamped value read from an NVML sample buffer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricSample {
+    /// CPU timestamp in microseconds since the Unix epoch
+    pub timestamp: u64,
+    /// Sampled value
+    pub value: f64,
+}
+
+/// Reason for reduced GPU clocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ThrottleReason {
+    /// Nothing is running on the GPU
+    GpuIdle,
+    /// Application or locked clocks are set below the maximum
+    ApplicationsClocksSetting,
+    /// Software power cap (power limit)
+    SwPowerCap,
+    /// Hardware slowdown (high temperature or power draw)
+    HwSlowdown,
+    /// Clocks synchronized with other GPUs in a sync boost group
+    SyncBoost,
+    /// Software thermal slowdown
+    SwThermalSlowdown,
+    /// Hardware thermal slowdown
+    HwThermalSlowdown,
+    /// External power brake (e.g. from the power supply)
+    HwPowerBrakeSlowdown,
+    /// Display clock setting
+    DisplayClockSetting,
+    /// Software slowdown because memory reached its own temperature limit
+    ///
+    /// Not an NVML bit: see [`classify_memory_thermal`](Self::classify_memory_thermal).
+    MemoryThermalSlowdown,
+}
+
+impl ThrottleReason {
+    /// All reasons with their NVML bit (`nvmlClocksThrottleReason*`)
+    const BITS: [(u64, ThrottleReason); 9] = [
+        (0x1, ThrottleReason::GpuIdle),
+        (0x2, ThrottleReason::ApplicationsClocksSetting),
+        (0x4, ThrottleReason::SwPowerCap),
+        (0x8, ThrottleReason::HwSlowdown),
+        (0x10, ThrottleReason::SyncBoost),
+        (0x20, ThrottleReason::SwThermalSlowdown),
+        (0x40, ThrottleReason::HwThermalSlowdown),
+        (0x80, ThrottleReason::HwPowerBrakeSlowdown),
+        (0x100, ThrottleReason::DisplayClockSetting),
+    ];
+
+    /// Decode an NVML throttle reason bitmask
+    pub fn from_bits(bits: u64) -> Vec<ThrottleReason> {
+        Self::BITS
+            .iter()
+            .filter(|(bit, _)| bits & bit != 0)
+            .map(|(_, reason)| *reason)
+            .collect()
+    }
+
+    /// Get a human-readable label
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::GpuIdle => "idle",
+            Self::ApplicationsClocksSetting => "application clocks",
+            Self::SwPowerCap => "power cap",
+            Self::HwSlowdown => "hardware slowdown",
+            Self::SyncBoost => "sync boost",
+            Self::SwThermalSlowdown => "software thermal slowdown",
+            Self::HwThermalSlowdown => "hardware thermal slowdown",
+            Self::HwPowerBrakeSlowdown => "power brake",
+            Self::DisplayClockSetting => "display clocks",
+            Self::MemoryThermalSlowdown => "memory thermal slowdown",
+        }
+    }
+
+    /// Add [`MemoryThermalSlowdown`](Self::MemoryThermalSlowdown) when memory is at or past its slowdown threshold
+    ///
+    /// NVML reports throttling for memory heat under the software thermal
+    /// bit, the same as for core heat. Cards with hot GDDR6X (e.g. RTX
+    /// 3090/4090) hit the memory limit while the core is still cool, so the
+    /// crossed threshold is what tells the two apart.
+    pub fn classify_memory_thermal(reasons: &mut Vec<ThrottleReason>, memory_temp: Option<u32>, memory_max: Option<u32>) {
+        let hot = matches!((memory_temp, memory_max), (Some(temp), Some(max)) if temp >= max);
+        if hot && !reasons.contains(&Self::MemoryThermalSlowdown) {
+            reasons.push(Self::MemoryThermalSlowdown);
+        }
+    }
+}
+
+/// Temperature status categories
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TemperatureStatus {
+    /// Below 50°C
+    Cool,
+    /// 51-70°C
+    Normal,
+    /// 71-85°C
+    Warm,
+    /// Above 85°C
+    Hot,
+}
+
+impl TemperatureStatus {
+    /// Get color hint for UI (CSS color name)
+    pub fn color(&self) -> &'static str {
+        match self {
+            Self::Cool => "green",
+            Self::Normal => "blue",
+            Self::Warm => "orange",
+            Self::Hot => "red",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_idle() {
+        let idle = GpuMetrics {
+            gpu_utilization: 2,
+            memory_utilization: 0,
+            encoder_utilization: 0,
+            decoder_utilization: 0,
+            encoder_sampling_us: None,
+            decoder_sampling_us: None,
+            ofa_utilization: None,
+            jpeg_utilization: None,
+            temperature: 40,
+            power_usage: 0,
+            fan_speed: None,
+            fan_speeds: Vec::new(),
+            clock_graphics: 0,
+            clock_memory: 0,
+            clock_sm: 0,
+            pcie_replay_count: None,
+            temperatures: BTreeMap::new(),
+        };
+        assert!(idle.is_idle());
+        assert!(idle.is_fully_idle());
+
+        // A busy video engine is not idle; a busy copy only fails the strict check
+        let transcoding = GpuMetrics {
+            encoder_utilization: 60,
+            ..idle.clone()
+        };
+        assert!(!transcoding.is_idle());
+        let copying = GpuMetrics {
+            memory_utilization: 30,
+            ..idle.clone()
+        };
+        assert!(copying.is_idle());
+        assert!(!copying.is_fully_idle());
+    }
+}
@@ -1,263 +1,2047 @@
-//! GPU Monitor - main monitoring service
-
-use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
-use nvml_wrapper::Nvml;
-use std::fs;
-use std::path::Path;
-
-use crate::device::{DeviceInfo, MemoryInfo};
-use crate::error::{Error, Result};
-use crate::metrics::GpuMetrics;
-use crate::process::{GpuProcess, ProcessType};
-use crate::GpuInfo;
-
-/// GPU Monitor service
-///
-/// Provides methods to query GPU information through NVML.
-pub struct GpuMonitor {
-    nvml: Nvml,
-}
-
-impl GpuMonitor {
-    /// Create a new GPU monitor instance
-    ///
-    /// Initializes the NVML library. Returns an error if NVML
-    /// is not available (e.g., no NVIDIA drivers installed).
-    pub fn new() -> Result<Self> {
-        let nvml = Nvml::init().map_err(|e| Error::NvmlInit(e.to_string()))?;
-        Ok(Self { nvml })
-    }
-
-    /// Get the number of GPU devices
-    pub fn device_count(&self) -> Result<u32> {
-        Ok(self.nvml.device_count()?)
-    }
-
-    /// Get information for all GPU devices
-    pub fn get_all_gpu_info(&self) -> Result<Vec<GpuInfo>> {
-        let count = self.device_count()?;
-        if count == 0 {
-            return Err(Error::NoDevices);
-        }
-
-        let mut gpus = Vec::with_capacity(count as usize);
-        for i in 0..count {
-            gpus.push(self.get_gpu_info(i)?);
-        }
-        Ok(gpus)
-    }
-
-    /// Get information for a specific GPU device
-    pub fn get_gpu_info(&self, index: u32) -> Result<GpuInfo> {
-        let device = self.nvml.device_by_index(index)?;
-
-        // Get device info
-        let name = device.name()?;
-        let uuid = device.uuid()?;
-        let pci_info = device.pci_info()?;
-        let pci_bus_id = pci_info.bus_id;
-
-        // Get driver version from NVML
-        let driver_version = self.nvml.sys_driver_version()?;
-
-        // Get CUDA version (returns version as integer like 12020 for 12.2)
-        let cuda_version = self
-            .nvml
-            .sys_cuda_driver_version()
-            .ok()
-            .map(|v| {
-                let major = v / 1000;
-                let minor = (v % 1000) / 10;
-                format!("{}.{}", major, minor)
-            });
-
-        // Get power info
-        let power_limit = device.power_management_limit().unwrap_or(0) / 1000; // mW to W
-        let power_limit_max = device.power_management_limit_constraints()
-            .map(|c| c.max_limit / 1000)
-            .unwrap_or(power_limit);
-
-        let device_info = DeviceInfo {
-            index,
-            name,
-            uuid,
-            pci_bus_id,
-            driver_version,
-            cuda_version,
-            power_limit,
-            power_limit_max,
-        };
-
-        // Get memory info
-        let mem_info = device.memory_info()?;
-        let memory = MemoryInfo {
-            total: mem_info.total,
-            used: mem_info.used,
-            free: mem_info.free,
-        };
-
-        // Get utilization
-        let utilization = device.utilization_rates()?;
-        let gpu_utilization = utilization.gpu;
-        let memory_utilization = utilization.memory;
-
-        // Get encoder/decoder utilization
-        let encoder_info = device.encoder_utilization().ok();
-        let encoder_utilization = encoder_info.map(|e| e.utilization).unwrap_or(0);
-        
-        let decoder_info = device.decoder_utilization().ok();
-        let decoder_utilization = decoder_info.map(|d| d.utilization).unwrap_or(0);
-
-        // Get temperature
-        let temperature = device
-            .temperature(TemperatureSensor::Gpu)
-            .unwrap_or(0);
-
-        // Get power usage
-        let power_usage = device.power_usage().unwrap_or(0);
-
-        // Get fan speed (may not be available on all GPUs)
-        let fan_speed = device.fan_speed(0).ok();
-
-        // Get clock speeds
-        let clock_graphics = device
-            .clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics)
-            .unwrap_or(0);
-        let clock_memory = device
-            .clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory)
-            .unwrap_or(0);
-        let clock_sm = device
-            .clock_info(nvml_wrapper::enum_wrappers::device::Clock::SM)
-            .unwrap_or(0);
-
-        let metrics = GpuMetrics {
-            gpu_utilization,
-            memory_utilization,
-            encoder_utilization,
-            decoder_utilization,
-            temperature,
-            power_usage,
-            fan_speed,
-            clock_graphics,
-            clock_memory,
-            clock_sm,
-        };
-
-        // Get processes
-        let processes = self.get_gpu_processes(&device)?;
-
-        Ok(GpuInfo {
-            device: device_info,
-            metrics,
-            memory,
-            processes,
-        })
-    }
-
-    /// Get processes using a GPU device
-    fn get_gpu_processes(
-        &self,
-        device: &nvml_wrapper::Device,
-    ) -> Result<Vec<GpuProcess>> {
-        let mut processes = Vec::new();
-
-        // Get compute processes
-        if let Ok(compute_procs) = device.running_compute_processes() {
-            for proc in compute_procs {
-                let name = get_process_name(proc.pid).unwrap_or_else(|| "unknown".to_string());
-                let memory = extract_gpu_memory(proc.used_gpu_memory);
-                processes.push(GpuProcess {
-                    pid: proc.pid,
-                    name,
-                    gpu_memory: memory,
-                    process_type: ProcessType::Compute,
-                });
-            }
-        }
-
-        // Get graphics processes
-        if let Ok(graphics_procs) = device.running_graphics_processes() {
-            for proc in graphics_procs {
-                let memory = extract_gpu_memory(proc.used_gpu_memory);
-                // Check if we already have this process as compute
-                if let Some(existing) = processes.iter_mut().find(|p| p.pid == proc.pid) {
-                    existing.process_type = ProcessType::Mixed;
-                    existing.gpu_memory = existing.gpu_memory.max(memory);
-                } else {
-                    let name =
-                        get_process_name(proc.pid).unwrap_or_else(|| "unknown".to_string());
-                    processes.push(GpuProcess {
-                        pid: proc.pid,
-                        name,
-                        gpu_memory: memory,
-                        process_type: ProcessType::Graphics,
-                    });
-                }
-            }
-        }
-
-        // Sort by memory usage (descending)
-        processes.sort_by(|a, b| b.gpu_memory.cmp(&a.gpu_memory));
-
-        Ok(processes)
-    }
-}
-
-/// Extract GPU memory value from UsedGpuMemory enum
-fn extract_gpu_memory(used: nvml_wrapper::enums::device::UsedGpuMemory) -> u64 {
-    use nvml_wrapper::enums::device::UsedGpuMemory;
-    match used {
-        UsedGpuMemory::Used(bytes) => bytes,
-        UsedGpuMemory::Unavailable => 0,
-    }
-}
-
-/// Get process name from PID by reading /proc/{pid}/comm
-fn get_process_name(pid: u32) -> Option<String> {
-    let comm_path = Path::new("/proc").join(pid.to_string()).join("comm");
-    fs::read_to_string(comm_path)
-        .ok()
-        .map(|s| s.trim().to_string())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_memory_info_calculations() {
-        let mem = MemoryInfo {
-            total: 8 * 1024 * 1024 * 1024, // 8 GB
-            used: 2 * 1024 * 1024 * 1024,  // 2 GB
-            free: 6 * 1024 * 1024 * 1024,  // 6 GB
-        };
-
-        assert_eq!(mem.total_mib(), 8192);
-        assert_eq!(mem.used_mib(), 2048);
-        assert_eq!(mem.free_mib(), 6144);
-        assert!((mem.usage_percent() - 25.0).abs() < 0.01);
-    }
-
-    #[test]
-    fn test_temperature_status() {
-        let cool = GpuMetrics {
-            gpu_utilization: 0,
-            memory_utilization: 0,
-            encoder_utilization: 0,
-            decoder_utilization: 0,
-            temperature: 40,
-            power_usage: 0,
-            fan_speed: None,
-            clock_graphics: 0,
-            clock_memory: 0,
-            clock_sm: 0,
-        };
-        assert_eq!(cool.temperature_status(), crate::metrics::TemperatureStatus::Cool);
-
-        let hot = GpuMetrics {
-            temperature: 90,
-            ..cool.clone()
-        };
-        assert_eq!(hot.temperature_status(), crate::metrics::TemperatureStatus::Hot);
-    }
-}
+//! GPU Monitor - main monitoring service
+
+use nvml_wrapper::enum_wrappers::device::{
+    Clock, EncoderType, TemperatureSensor, TemperatureThreshold, TopologyLevel,
+};
+use nvml_wrapper::Nvml;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::device::{
+    Capabilities, ClockRange, ClockState, DeviceInfo, DriverModel, FanPolicy, MemoryInfo, MigInstance,
+    RemappedRows, RetiredPages,
+};
+use crate::error::{Error, Result};
+use crate::metrics::{GpuMetrics, MetricSample, ThrottleReason};
+use crate::process::{AccountingRecord, GpuProcess, ProcessMemoryTree, ProcessType, UNKNOWN_OTHER_NAMESPACE};
+use crate::raw::{GpuInstanceProfile, RawNvml};
+use crate::stream::Samples;
+use crate::topology::{CpuRange, LinkType, Topology};
+#[cfg(all(feature = "xid", target_os = "linux"))]
+use crate::xid::{XidEvent, XidWatcher};
+use crate::GpuInfo;
+
+/// Process names of X servers and Wayland compositors
+const DISPLAY_SERVERS: &[&str] = &[
+    "Xorg",
+    "X",
+    "Xwayland",
+    "gnome-shell",
+    "kwin_x11",
+    "kwin_wayland",
+    "mutter",
+    "sway",
+    "Hyprland",
+    "weston",
+    "cinnamon",
+    "xfwm4",
+];
+
+/// Maximum number of NVLinks per device (NVML_NVLINK_MAX_LINKS)
+const NVLINK_MAX_LINKS: u32 = 18;
+
+/// Memory bus width in bits from which memory is HBM (GDDR buses are at most 512 bits)
+const HBM_MIN_BUS_WIDTH: u32 = 1024;
+
+/// Words in the CPU affinity mask, enough for 1024 CPUs (glibc CPU_SETSIZE)
+#[cfg(target_os = "linux")]
+const CPU_SET_WORDS: usize = 1024 / std::os::raw::c_ulong::BITS as usize;
+
+/// What to collect in each sample, see [`GpuMonitor::get_all_gpu_info_opts`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Enumerate processes; when false, `processes` is empty, `process_count` is 0 and
+    /// `processes_collected` is false
+    pub processes: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { processes: true }
+    }
+}
+
+/// Configuration for a [`GpuMonitor`], see [`GpuMonitor::builder`]
+///
+/// ```no_run
+/// # use gpu_monitor_core::GpuMonitor;
+/// # use std::time::Duration;
+/// let monitor = GpuMonitor::builder()
+///     .min_interval(Duration::from_millis(500))
+///     .skip_processes(true)
+///     .build()?;
+/// # Ok::<(), gpu_monitor_core::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct GpuMonitorBuilder {
+    min_interval: Duration,
+    max_retries: u32,
+    retry_backoff: Duration,
+    max_processes: Option<usize>,
+    options: Options,
+}
+
+impl Default for GpuMonitorBuilder {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_millis(100),
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(10),
+            max_processes: None,
+            options: Options::default(),
+        }
+    }
+}
+
+impl GpuMonitorBuilder {
+    /// Minimum time between two NVML samples, i.e. how long a sample is cached (default 100 ms)
+    pub fn min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Maximum number of retries for transient NVML errors (default 2)
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled on each further attempt (default 10 ms)
+    pub fn retry_backoff(mut self, retry_backoff: Duration) -> Self {
+        self.retry_backoff = retry_backoff;
+        self
+    }
+
+    /// Resolve at most this many processes per GPU, highest memory first (default all)
+    pub fn max_processes(mut self, max_processes: usize) -> Self {
+        self.max_processes = Some(max_processes);
+        self
+    }
+
+    /// Skip process enumeration in [`GpuMonitor::get_all_gpu_info`] and [`GpuMonitor::get_gpu_info`]
+    pub fn skip_processes(mut self, skip: bool) -> Self {
+        self.options.processes = !skip;
+        self
+    }
+
+    /// Initialize NVML and create the monitor
+    pub fn build(self) -> Result<GpuMonitor> {
+        let nvml = Nvml::init().map_err(|e| Error::NvmlInit(e.to_string()))?;
+        Ok(GpuMonitor {
+            nvml,
+            raw: RawNvml::load(),
+            max_retries: self.max_retries,
+            retry_backoff: self.retry_backoff,
+            max_processes: self.max_processes,
+            min_interval: self.min_interval,
+            options: self.options,
+            last_sample: Mutex::new(None),
+            process_identities: Mutex::new(HashMap::new()),
+            nested_pid_namespace: in_nested_pid_namespace(),
+            capabilities: Mutex::new(HashMap::new()),
+            clock_ranges: Mutex::new(HashMap::new()),
+            mig_profiles: Mutex::new(HashMap::new()),
+            utilization_seen: Mutex::new(HashMap::new()),
+            process_activity: Mutex::new(HashMap::new()),
+            power_samples_seen: Mutex::new(HashMap::new()),
+            #[cfg(all(feature = "xid", target_os = "linux"))]
+            xid: Mutex::new(XidWatcher::new()),
+        })
+    }
+}
+
+/// GPU Monitor service
+///
+/// Provides methods to query GPU information through NVML.
+pub struct GpuMonitor {
+    nvml: Nvml,
+    /// NVML functions not wrapped by nvml-wrapper, None if unavailable
+    raw: Option<RawNvml>,
+    /// Maximum number of retries for transient NVML errors
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each further attempt
+    pub retry_backoff: Duration,
+    /// Maximum number of processes resolved per GPU (highest memory first), None for all
+    pub max_processes: Option<usize>,
+    /// Minimum time between two NVML samples of all GPUs
+    min_interval: Duration,
+    /// What `get_all_gpu_info` and `get_gpu_info` collect
+    options: Options,
+    /// Last sample of all GPUs and the options it was taken with, returned if queried again within `min_interval`
+    last_sample: Mutex<Option<(Instant, Options, Vec<GpuInfo>)>>,
+    /// Cached process names, owners and command lines keyed by PID
+    process_identities: Mutex<HashMap<u32, ProcessIdentity>>,
+    /// Running in a nested PID namespace, where `/proc` does not show NVML's host PIDs
+    nested_pid_namespace: bool,
+    /// Supported features per device index, probed on first use
+    capabilities: Mutex<HashMap<u32, Capabilities>>,
+    /// Supported clock range per device index, read on first use
+    clock_ranges: Mutex<HashMap<u32, Option<ClockRange>>>,
+    /// MIG GPU instance profiles per device index, read on first use
+    mig_profiles: Mutex<HashMap<u32, Vec<GpuInstanceProfile>>>,
+    /// Newest process utilization sample timestamp (µs) seen per device index
+    utilization_seen: Mutex<HashMap<u32, u64>>,
+    /// Activity of each process (by PID) per device index, from the latest snapshot
+    process_activity: Mutex<HashMap<u32, HashMap<u32, ProcessActivity>>>,
+    /// Newest power sample timestamp (µs) seen per device index
+    power_samples_seen: Mutex<HashMap<u32, u64>>,
+    /// Kernel log reader for XID events
+    #[cfg(all(feature = "xid", target_os = "linux"))]
+    xid: Mutex<XidWatcher>,
+}
+
+impl GpuMonitor {
+    /// Create a new GPU monitor instance
+    ///
+    /// Initializes the NVML library with the default configuration. Returns
+    /// an error if NVML is not available (e.g., no NVIDIA drivers installed).
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    /// Configure a monitor before creating it
+    pub fn builder() -> GpuMonitorBuilder {
+        GpuMonitorBuilder::default()
+    }
+
+    /// Set the minimum time between two NVML samples
+    ///
+    /// `get_all_gpu_info` returns the cached sample (with its original
+    /// `sampled_at` timestamp) when called again sooner than this, to avoid
+    /// excessive polling of the driver. Defaults to 100 ms.
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Check if this process runs in a nested PID namespace, e.g. in a container
+    ///
+    /// NVML reports host PIDs, which `/proc` in a nested namespace does not
+    /// show, so processes started outside the container cannot be resolved
+    /// and are named [`UNKNOWN_OTHER_NAMESPACE`]. Best effort: false if the
+    /// namespace cannot be determined.
+    pub fn nested_pid_namespace(&self) -> bool {
+        self.nested_pid_namespace
+    }
+
+    /// Get the number of GPU devices
+    pub fn device_count(&self) -> Result<u32> {
+        Ok(self.nvml.device_count()?)
+    }
+
+    /// Get information for all GPU devices
+    ///
+    /// Returns the previous sample if called within the minimum interval.
+    pub fn get_all_gpu_info(&self) -> Result<Vec<GpuInfo>> {
+        self.get_all_gpu_info_opts(self.options)
+    }
+
+    /// Get information for all GPU devices, collecting only what `options` asks for
+    ///
+    /// Resolving processes is the most expensive part of a sample, so metrics-only
+    /// consumers can skip it with `Options { processes: false }`. Returns the previous
+    /// sample if called within the minimum interval and it holds everything asked for.
+    pub fn get_all_gpu_info_opts(&self, options: Options) -> Result<Vec<GpuInfo>> {
+        if let Ok(last) = self.last_sample.lock() {
+            if let Some((at, sampled_with, gpus)) = last.as_ref() {
+                if at.elapsed() < self.min_interval && (sampled_with.processes || !options.processes) {
+                    let mut gpus = gpus.clone();
+                    if !options.processes {
+                        for gpu in &mut gpus {
+                            gpu.processes.clear();
+                            gpu.process_count = 0;
+                            gpu.processes_collected = false;
+                        }
+                    }
+                    return Ok(gpus);
+                }
+            }
+        }
+
+        let gpus = self.sample_all_gpus(options)?;
+        if let Ok(mut last) = self.last_sample.lock() {
+            *last = Some((Instant::now(), options, gpus.clone()));
+        }
+        Ok(gpus)
+    }
+
+    /// Query NVML for information on all GPU devices
+    fn sample_all_gpus(&self, options: Options) -> Result<Vec<GpuInfo>> {
+        let count = self.device_count()?;
+        if count == 0 {
+            return Err(Error::NoDevices);
+        }
+
+        // Share one timestamp across all GPUs in this sample
+        let sampled_at = unix_millis_now();
+
+        let mut gpus = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            gpus.push(self.with_retry(|| self.read_gpu_info(i, sampled_at, options))?);
+        }
+
+        // Drop cached identities of processes that are no longer running on any GPU
+        if !options.processes {
+            return Ok(gpus);
+        }
+        if let Ok(mut identities) = self.process_identities.lock() {
+            identities.retain(|pid, _| {
+                gpus.iter()
+                    .any(|g| g.processes.iter().any(|p| p.pid == *pid))
+            });
+        }
+
+        Ok(gpus)
+    }
+
+    /// Stream snapshots of all GPUs, one per `interval`
+    ///
+    /// The first snapshot is taken immediately; each further call to `next`
+    /// sleeps until the next tick. The iterator never ends on its own.
+    ///
+    /// ```no_run
+    /// # use gpu_monitor_core::GpuMonitor;
+    /// # use std::time::Duration;
+    /// let monitor = GpuMonitor::new()?;
+    /// for snapshot in monitor.samples(Duration::from_secs(1)).take(10) {
+    ///     println!("{} GPUs", snapshot?.gpus.len());
+    /// }
+    /// # Ok::<(), gpu_monitor_core::Error>(())
+    /// ```
+    pub fn samples(&self, interval: Duration) -> Samples<'_> {
+        Samples::new(self, interval)
+    }
+
+    /// Get information for a specific GPU device
+    ///
+    /// Returns [`Error::InvalidDevice`] if there is no GPU with this index.
+    pub fn get_gpu_info(&self, index: u32) -> Result<GpuInfo> {
+        if index >= self.device_count()? {
+            return Err(Error::InvalidDevice(index));
+        }
+        self.with_retry(|| self.read_gpu_info(index, unix_millis_now(), self.options))
+    }
+
+    /// Get information for the GPU that CUDA applications see as `cuda:<ordinal>`
+    ///
+    /// See [`GpuMonitor::cuda_ordinal_to_index`] for how the ordinal is resolved.
+    pub fn get_gpu_info_by_cuda_ordinal(&self, ordinal: u32) -> Result<GpuInfo> {
+        let index = self.cuda_ordinal_to_index(ordinal)?;
+        self.get_gpu_info(index)
+    }
+
+    /// Resolve a CUDA device ordinal to an NVML device index
+    ///
+    /// Without `CUDA_VISIBLE_DEVICES` the ordinal is taken to be the NVML index,
+    /// which holds when `CUDA_DEVICE_ORDER=PCI_BUS_ID` (NVML enumerates by PCI
+    /// bus ID, CUDA by default puts the fastest device first). Otherwise the
+    /// variable is read the way the CUDA runtime does: a comma-separated list of
+    /// NVML indices or `GPU-` UUIDs (a unique prefix is enough), where the list
+    /// ends at the first entry that is invalid, unknown, ambiguous or repeated.
+    /// `MIG-` UUIDs are not supported and also end the list.
+    pub fn cuda_ordinal_to_index(&self, ordinal: u32) -> Result<u32> {
+        let count = self.device_count()?;
+        let uuids = (0..count)
+            .map(|i| Ok(self.nvml.device_by_index(i)?.uuid()?))
+            .collect::<Result<Vec<String>>>()?;
+        let visible = std::env::var("CUDA_VISIBLE_DEVICES").ok();
+        resolve_cuda_ordinal(visible.as_deref(), ordinal, &uuids)
+            .ok_or(Error::InvalidCudaDevice(ordinal))
+    }
+
+    /// Get the optional features supported by a GPU device
+    ///
+    /// Probed once per device and cached, since support does not change at runtime.
+    /// Fan, clock and power controls are reported unsupported while MIG is
+    /// enabled, as NVML rejects them on the whole GPU in that mode; the cache is
+    /// cleared by [`reset_gpu`](Self::reset_gpu), which is when a MIG change applies.
+    pub fn capabilities(&self, index: u32) -> Result<Capabilities> {
+        if let Some(caps) = self.capabilities.lock().ok().and_then(|c| c.get(&index).copied()) {
+            return Ok(caps);
+        }
+
+        let device = self.nvml.device_by_index(index)?;
+        let mig_mode = self.raw.as_ref().and_then(|raw| raw.mig_mode(&device).ok());
+        let mig_enabled = mig_mode.is_some_and(|(current, _)| {
+            use nvml_wrapper_sys::bindings::NVML_DEVICE_MIG_ENABLE;
+            current == NVML_DEVICE_MIG_ENABLE
+        });
+        let caps = Capabilities {
+            has_ecc: device.is_ecc_enabled().is_ok(),
+            has_nvlink: (0..NVLINK_MAX_LINKS)
+                .any(|link| device.link_wrapper_for(link).is_active().is_ok()),
+            has_mig: mig_mode.is_some(),
+            has_power_limit_control: !mig_enabled
+                && device
+                    .power_management_limit_constraints()
+                    .is_ok_and(|c| c.min_limit < c.max_limit),
+            has_fan_control: !mig_enabled
+                && device.fan_speed(0).is_ok()
+                && self
+                    .raw
+                    .as_ref()
+                    .is_some_and(|raw| raw.fan_control_policy(&device, 0).is_ok()),
+            has_clock_control: !mig_enabled && self.clock_range(index, &device).is_some(),
+            has_encoder: device.encoder_capacity(EncoderType::H264).is_ok(),
+            has_decoder: device.decoder_utilization().is_ok(),
+            has_remapped_rows: read_remapped_rows(&device).is_ok(),
+            has_persistence_control: get_persistence_mode(&device).is_some(),
+            has_reset: get_driver_model(&device) != Some(DriverModel::Wddm),
+        };
+
+        if let Ok(mut cache) = self.capabilities.lock() {
+            cache.insert(index, caps);
+        }
+        Ok(caps)
+    }
+
+    /// Get processes using a specific GPU device (highest memory first)
+    ///
+    /// `active` and `sm_utilization` come from the latest snapshot, so this
+    /// does not take utilization samples away from [`get_all_gpu_info`](Self::get_all_gpu_info).
+    pub fn get_processes(&self, index: u32) -> Result<Vec<GpuProcess>> {
+        let device = self.nvml.device_by_index(index)?;
+        Ok(self.get_gpu_processes(&device, false)?.0)
+    }
+
+    /// Get the processes recorded in a device's accounting buffer, most recent first
+    ///
+    /// Unlike [`get_processes`](Self::get_processes) this includes processes
+    /// that have exited, as long as the driver's buffer still holds them.
+    /// Fails with [`Error::AccountingDisabled`] unless accounting mode is on.
+    pub fn get_accounting_pids(&self, index: u32) -> Result<Vec<AccountingRecord>> {
+        let device = self.nvml.device_by_index(index)?;
+        read_accounting(&device, index).map_err(|e| e.on_device(index))
+    }
+
+    /// Terminate a process, refusing PIDs that are not using any GPU
+    ///
+    /// Checks NVML's full process lists, so processes beyond
+    /// [`max_processes`](Self::max_processes) can be terminated too.
+    pub fn kill_process(&self, pid: u32) -> Result<()> {
+        let mut found = false;
+        for i in 0..self.device_count()? {
+            let device = self.nvml.device_by_index(i)?;
+            let compute = device.running_compute_processes().unwrap_or_default();
+            let graphics = device.running_graphics_processes().unwrap_or_default();
+            if compute.iter().chain(&graphics).any(|p| p.pid == pid) {
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            return Err(Error::ProcessNotFound(pid));
+        }
+        crate::process::kill_process(pid)
+    }
+
+    /// Reset a GPU device
+    ///
+    /// NVML has no public reset call, so this runs `nvidia-smi --gpu-reset`,
+    /// which requires root. Unless `force` is set, refuses to reset a GPU
+    /// that has running processes or drives a display.
+    pub fn reset_gpu(&self, index: u32, force: bool) -> Result<()> {
+        let device = self.nvml.device_by_index(index)?;
+        if !force {
+            let (_, process_count) = self.get_gpu_processes(&device, false)?;
+            if process_count > 0 || device.is_display_active().unwrap_or(false) {
+                return Err(Error::GpuBusy(index));
+            }
+        }
+
+        let bus_id = device.pci_info()?.bus_id;
+        let output = std::process::Command::new("nvidia-smi")
+            .args(["--gpu-reset", "-i", &bus_id])
+            .output()?;
+
+        // The cached sample describes the device before the reset
+        if let Ok(mut last) = self.last_sample.lock() {
+            *last = None;
+        }
+        // A pending MIG mode change takes effect on reset and changes the available controls
+        if let Ok(mut cache) = self.capabilities.lock() {
+            cache.remove(&index);
+        }
+        if let Ok(mut cache) = self.mig_profiles.lock() {
+            cache.remove(&index);
+        }
+
+        // nvidia-smi exit codes: 3 = not supported, 4 = insufficient permissions
+        match output.status.code() {
+            Some(0) => Ok(()),
+            Some(3) => Err(nvml_wrapper::error::NvmlError::NotSupported.into()),
+            Some(4) => Err(Error::ResetDenied(index)),
+            _ => Err(Error::ResetFailed {
+                index,
+                reason: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+            }),
+        }
+    }
+
+    /// Enable or disable persistence mode on a GPU
+    ///
+    /// Keeps the driver loaded while no application uses the GPU, which avoids
+    /// the driver initialization delay on the next launch. Requires root and is
+    /// only supported on Linux.
+    pub fn set_persistence_mode(&self, index: u32, enabled: bool) -> Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            use nvml_wrapper::error::NvmlError;
+
+            let mut device = self.nvml.device_by_index(index)?;
+            device.set_persistent(enabled).map_err(|e| match e {
+                NvmlError::NoPermission => Error::PersistenceDenied(index),
+                e => e.into(),
+            })?;
+
+            // The cached sample reports the previous mode
+            if let Ok(mut last) = self.last_sample.lock() {
+                *last = None;
+            }
+            Ok(())
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (index, enabled);
+            Err(nvml_wrapper::error::NvmlError::NotSupported.into())
+        }
+    }
+
+    /// Get power samples (in watts) recorded by the driver since the previous call
+    ///
+    /// NVML buffers power readings at a higher rate than is practical to poll,
+    /// so this gives sub-second resolution at the cost of one query. The first
+    /// call returns the whole buffer; an empty vec means no new samples.
+    /// NVML keeps no such buffer for temperature, which must be polled.
+    pub fn get_power_samples(&self, index: u32) -> Result<Vec<MetricSample>> {
+        use nvml_wrapper::enum_wrappers::device::Sampling;
+        use nvml_wrapper::enums::device::SampleValue;
+        use nvml_wrapper::error::NvmlError;
+
+        let device = self.nvml.device_by_index(index)?;
+        let last_seen = self.power_samples_seen.lock().ok().and_then(|seen| seen.get(&index).copied());
+
+        let samples = match device.samples(Sampling::Power, last_seen) {
+            Ok(samples) => samples,
+            // Nothing was sampled since the last query
+            Err(NvmlError::NotFound) => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(newest) = samples.iter().map(|s| s.timestamp).max() {
+            if let Ok(mut seen) = self.power_samples_seen.lock() {
+                seen.insert(index, newest);
+            }
+        }
+
+        Ok(samples
+            .into_iter()
+            .map(|sample| {
+                let milliwatts = match sample.value {
+                    SampleValue::F64(v) => v,
+                    SampleValue::U32(v) => v as f64,
+                    SampleValue::U64(v) => v as f64,
+                    SampleValue::I64(v) => v as f64,
+                };
+                MetricSample {
+                    timestamp: sample.timestamp,
+                    value: milliwatts / 1000.0,
+                }
+            })
+            .collect())
+    }
+
+    /// Get remapped memory row counts for a specific GPU device
+    pub fn get_remapped_rows(&self, index: u32) -> Result<RemappedRows> {
+        let device = self.nvml.device_by_index(index)?;
+        read_remapped_rows(&device)
+    }
+
+    /// Get the GPU memory of a device's processes grouped by owning user
+    pub fn process_memory_tree(&self, index: u32) -> Result<ProcessMemoryTree> {
+        let device = self.nvml.device_by_index(index)?;
+        let (processes, _) = self.get_gpu_processes(&device, false)?;
+        let owned = processes.into_iter().map(|p| (p.user.clone(), p)).collect();
+        Ok(ProcessMemoryTree::from_owned(index, owned))
+    }
+
+    /// Get the interconnect topology between all GPU devices
+    pub fn get_topology(&self) -> Result<Topology> {
+        let count = self.device_count()?;
+        if count == 0 {
+            return Err(Error::NoDevices);
+        }
+
+        // Collect PCI bus IDs and NVLink peers for each device
+        let mut bus_ids = Vec::with_capacity(count as usize);
+        let mut nvlink_peers = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let device = self.nvml.device_by_index(i)?;
+            bus_ids.push(device.pci_info()?.bus_id);
+            nvlink_peers.push(get_nvlink_peers(&device));
+        }
+
+        let mut links = Vec::with_capacity(count as usize);
+        for a in 0..count {
+            let mut row = Vec::with_capacity(count as usize);
+            for b in 0..count {
+                let link = if a == b {
+                    LinkType::Same
+                } else {
+                    let nvlinks = nvlink_peers[a as usize]
+                        .iter()
+                        .filter(|peer| **peer == bus_ids[b as usize])
+                        .count() as u32;
+                    if nvlinks > 0 {
+                        LinkType::NvLink(nvlinks)
+                    } else {
+                        let device = self.nvml.device_by_index(a)?;
+                        let other = self.nvml.device_by_index(b)?;
+                        match device.topology_common_ancestor(other) {
+                            Ok(TopologyLevel::Internal) => LinkType::Internal,
+                            Ok(TopologyLevel::Single) => LinkType::PcieSwitch,
+                            Ok(TopologyLevel::Multiple) => LinkType::PcieMultiSwitch,
+                            Ok(TopologyLevel::HostBridge) => LinkType::HostBridge,
+                            Ok(TopologyLevel::Node) => LinkType::Node,
+                            Ok(TopologyLevel::System) => LinkType::System,
+                            Err(_) => LinkType::Unknown,
+                        }
+                    }
+                };
+                row.push(link);
+            }
+            links.push(row);
+        }
+
+        Ok(Topology {
+            devices: (0..count).collect(),
+            links,
+        })
+    }
+
+    /// Run an operation, retrying transient NVML errors with exponential backoff
+    fn with_retry<T>(&self, mut op: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut attempt = 0;
+        loop {
+            match op() {
+                Err(e) if e.is_transient() && attempt < self.max_retries => {
+                    let delay = self.retry_backoff * 2u32.pow(attempt);
+                    tracing::debug!("Transient NVML error, retrying in {:?}: {}", delay, e);
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Get the CPU cores closest to a GPU, as ranges for `taskset -c`
+    ///
+    /// Pin threads that feed the GPU (e.g. data loaders) to these cores to
+    /// keep host-to-device copies on the GPU's local NUMA node. Linux only.
+    pub fn recommended_cpu_set(&self, index: u32) -> Result<Vec<CpuRange>> {
+        #[cfg(target_os = "linux")]
+        {
+            use crate::topology::cpu_ranges;
+            use std::os::raw::c_ulong;
+
+            let device = self.nvml.device_by_index(index)?;
+            // c_ulong is only 32 bits on 32-bit targets
+            #[allow(clippy::useless_conversion)]
+            let mask: Vec<u64> = device
+                .cpu_affinity(CPU_SET_WORDS)?
+                .into_iter()
+                .map(u64::from)
+                .collect();
+            Ok(cpu_ranges(&mask, c_ulong::BITS))
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = index;
+            Err(nvml_wrapper::error::NvmlError::NotSupported.into())
+        }
+    }
+
+    /// Read information for a GPU device, stamped with the given sample time
+    ///
+    /// NVML errors name the device, see [`Error::Device`].
+    fn read_gpu_info(&self, index: u32, sampled_at: u64, options: Options) -> Result<GpuInfo> {
+        self.read_device(index, sampled_at, options).map_err(|e| e.on_device(index))
+    }
+
+    /// Body of [`read_gpu_info`](Self::read_gpu_info), with NVML errors not yet tagged
+    fn read_device(&self, index: u32, sampled_at: u64, options: Options) -> Result<GpuInfo> {
+        let device = self.nvml.device_by_index(index)?;
+
+        // Get device info
+        let name = device.name()?;
+        let uuid = device.uuid()?;
+        let pci_info = device.pci_info()?;
+        let pci_bus_id = pci_info.bus_id;
+
+        // Get minor number to reconcile with nvidia-smi / /dev/nvidiaN ordering
+        let minor_number = device.minor_number().ok();
+
+        // Get board ID (multi-die cards report the same ID for each device)
+        let board_id = device.board_id().ok();
+
+        // Get the local NUMA node for NUMA-aware thread placement
+        let numa_node = get_numa_node(&pci_bus_id);
+
+        // Get driver version from NVML
+        let driver_version = self.nvml.sys_driver_version()?;
+
+        // Get VBIOS version (used to flag metrics with known issues)
+        let vbios_version = device.vbios_version().ok();
+
+        // Get CUDA version (returns version as integer like 12020 for 12.2)
+        let cuda_version = self
+            .nvml
+            .sys_cuda_driver_version()
+            .ok()
+            .map(|v| {
+                let major = v / 1000;
+                let minor = (v % 1000) / 10;
+                format!("{}.{}", major, minor)
+            });
+
+        // Get power info
+        let power_limit = device.power_management_limit().unwrap_or(0) / 1000; // mW to W
+        let power_limit_max = device.power_management_limit_constraints()
+            .map(|c| c.max_limit / 1000)
+            .unwrap_or(power_limit);
+
+        // Get thermal thresholds
+        let temp_slowdown = device.temperature_threshold(TemperatureThreshold::Slowdown).ok();
+        let temp_shutdown = device.temperature_threshold(TemperatureThreshold::Shutdown).ok();
+        let temp_memory_max = device.temperature_threshold(TemperatureThreshold::MemoryMax).ok();
+
+        // Video engines are probed once (compute-only cards like A100 have no NVENC)
+        let capabilities = self.capabilities(index)?;
+        let has_encoder = capabilities.has_encoder;
+        let has_decoder = capabilities.has_decoder;
+
+        // Passively cooled cards report no fans (fall back to probing the first fan)
+        let has_fan = match device.num_fans() {
+            Ok(count) => count > 0,
+            Err(_) => device.fan_speed(0).is_ok(),
+        };
+
+        // Get fan control policy
+        let fan_control_policy = self
+            .raw
+            .as_ref()
+            .and_then(|raw| raw.fan_control_policy(&device, 0).ok())
+            .map(|policy| match policy {
+                nvml_wrapper_sys::bindings::NVML_FAN_POLICY_MANUAL => FanPolicy::Manual,
+                _ => FanPolicy::Auto,
+            });
+
+        // Get MIG mode (enabled even if no instances have been created)
+        let (mig_enabled, mig_pending) = self
+            .raw
+            .as_ref()
+            .and_then(|raw| raw.mig_mode(&device).ok())
+            .map(|(current, pending)| {
+                use nvml_wrapper_sys::bindings::NVML_DEVICE_MIG_ENABLE;
+                (current == NVML_DEVICE_MIG_ENABLE, pending == NVML_DEVICE_MIG_ENABLE)
+            })
+            .unwrap_or((false, false));
+
+        // Get ECC mode (changes only take effect after a reboot)
+        let ecc_mode = device.is_ecc_enabled().ok();
+        let ecc_enabled_current = ecc_mode.as_ref().map(|m| m.currently_enabled);
+        let ecc_enabled_pending = ecc_mode.as_ref().map(|m| m.pending_enabled);
+
+        // Get supported clock range
+        let clock_range = self.clock_range(index, &device);
+
+        // Get current and default application clocks
+        let app_clock_graphics = device.applications_clock(Clock::Graphics).ok();
+        let app_clock_memory = device.applications_clock(Clock::Memory).ok();
+        let default_app_clock_graphics = device.default_applications_clock(Clock::Graphics).ok();
+        let default_app_clock_memory = device.default_applications_clock(Clock::Memory).ok();
+
+        // Get clock throttle reasons
+        let mut throttle_reasons = device
+            .current_throttle_reasons()
+            .map(|reasons| ThrottleReason::from_bits(reasons.bits()))
+            .unwrap_or_default();
+
+        // Work out whether clocks are pinned
+        let clock_state = ClockState::infer(app_clock_graphics, default_app_clock_graphics);
+
+        // Get base clocks, falling back to the P0 minimum when there are no application clocks
+        let clock_graphics_base = default_app_clock_graphics.or_else(|| {
+            self.pstate_base_clock(&device, nvml_wrapper_sys::bindings::nvmlClockType_enum_NVML_CLOCK_GRAPHICS)
+        });
+        let clock_memory_base = default_app_clock_memory.or_else(|| {
+            self.pstate_base_clock(&device, nvml_wrapper_sys::bindings::nvmlClockType_enum_NVML_CLOCK_MEM)
+        });
+
+        // Get the vendor's rated boost clock (Pascal and later)
+        let boost_clock_rated = device.max_customer_boost_clock(Clock::Graphics).ok();
+
+        // Get driver model (Windows only)
+        let driver_model = get_driver_model(&device);
+
+        // Get persistence mode (Linux only)
+        let persistence_mode = get_persistence_mode(&device);
+
+        let device_info = DeviceInfo {
+            index,
+            name,
+            uuid,
+            pci_bus_id,
+            minor_number,
+            board_id,
+            numa_node,
+            driver_version,
+            vbios_version,
+            cuda_version,
+            power_limit,
+            power_limit_max,
+            temp_slowdown,
+            temp_shutdown,
+            temp_memory_max,
+            has_encoder,
+            has_decoder,
+            has_fan,
+            mig_enabled,
+            mig_pending,
+            ecc_enabled_current,
+            ecc_enabled_pending,
+            fan_control_policy,
+            clock_range,
+            app_clock_graphics,
+            app_clock_memory,
+            default_app_clock_graphics,
+            default_app_clock_memory,
+            clock_state,
+            clock_graphics_base,
+            clock_memory_base,
+            boost_clock_rated,
+            persistence_mode,
+            driver_model,
+        };
+
+        // Get memory info
+        let mem_info = device.memory_info()?;
+        let reserved = self
+            .raw
+            .as_ref()
+            .and_then(|raw| raw.memory_info_v2(&device).ok())
+            .map(|m| m.reserved);
+        let bar1 = device.bar1_memory_info().ok();
+        let ecc_reserved = ecc_reserved(
+            mem_info.total,
+            device_info.ecc_enabled_current,
+            device.memory_bus_width().ok(),
+        );
+        let memory = MemoryInfo {
+            total: mem_info.total,
+            used: mem_info.used,
+            free: mem_info.free,
+            reserved,
+            ecc_reserved,
+            bar1_total: bar1.as_ref().map(|b| b.total),
+            bar1_used: bar1.as_ref().map(|b| b.used),
+        };
+
+        // Fetch the dynamic values NVML exposes as fields in one driver call
+        let fields = SampleFields::read(&device);
+
+        // Get remapped rows (Ampere and later)
+        let remapped_rows = fields.remapped_rows;
+
+        // Get retired pages (pre-Ampere ECC devices)
+        let retired_pages = read_retired_pages(&device).ok();
+
+        // Get utilization
+        let utilization = device.utilization_rates()?;
+        let gpu_utilization = utilization.gpu;
+        let memory_utilization = utilization.memory;
+
+        // Get encoder/decoder utilization
+        let encoder_info = device.encoder_utilization().ok();
+        let encoder_utilization = encoder_info.as_ref().map(|e| e.utilization).unwrap_or(0);
+        let encoder_sampling_us = encoder_info.map(|e| e.sampling_period);
+        
+        let decoder_info = device.decoder_utilization().ok();
+        let decoder_utilization = decoder_info.as_ref().map(|d| d.utilization).unwrap_or(0);
+        let decoder_sampling_us = decoder_info.map(|d| d.sampling_period);
+
+        // Get OFA/JPEG utilization (only newer drivers and cards have these engines)
+        let ofa_utilization = self.raw.as_ref().and_then(|raw| raw.ofa_utilization(&device).ok());
+        let jpeg_utilization = self.raw.as_ref().and_then(|raw| raw.jpeg_utilization(&device).ok());
+
+        // Get temperature
+        let temperature = device
+            .temperature(TemperatureSensor::Gpu)
+            .unwrap_or(0);
+        let temperatures = self.read_temperatures(&device, temperature, fields.memory_temperature);
+        ThrottleReason::classify_memory_thermal(
+            &mut throttle_reasons,
+            temperatures.get("memory").copied(),
+            device_info.temp_memory_max,
+        );
+
+        // Get power usage
+        let power_usage = fields.power_usage.unwrap_or(0);
+
+        // Get fan speeds (may not be available on all GPUs); fans of one card can differ
+        let fan_count = device.num_fans().unwrap_or(1);
+        let fan_speeds: Vec<u32> = (0..fan_count).filter_map(|fan| device.fan_speed(fan).ok()).collect();
+        let fan_speed = fan_speeds.first().copied();
+
+        // Get clock speeds
+        let clock_graphics = device
+            .clock_info(Clock::Graphics)
+            .unwrap_or(0);
+        let clock_memory = device
+            .clock_info(Clock::Memory)
+            .unwrap_or(0);
+        let clock_sm = device
+            .clock_info(Clock::SM)
+            .unwrap_or(0);
+
+        // Get PCIe replay counter (not supported on all GPUs)
+        let pcie_replay_count = fields.pcie_replay_count;
+
+        let metrics = GpuMetrics {
+            gpu_utilization,
+            memory_utilization,
+            encoder_utilization,
+            decoder_utilization,
+            encoder_sampling_us,
+            decoder_sampling_us,
+            ofa_utilization,
+            jpeg_utilization,
+            temperature,
+            power_usage,
+            fan_speed,
+            fan_speeds,
+            clock_graphics,
+            clock_memory,
+            clock_sm,
+            pcie_replay_count,
+            temperatures,
+        };
+
+        // Get the MIG slicing layout
+        let mig_instances = if device_info.mig_enabled { self.get_mig_instances(index, &device) } else { Vec::new() };
+
+        // Get processes
+        let (processes, process_count) = if options.processes {
+            self.get_gpu_processes(&device, true)?
+        } else {
+            (Vec::new(), 0)
+        };
+
+        // Best effort: NVML knows whether a display is initialized, the process list whether a desktop runs here
+        let driving_display = device.is_display_active().unwrap_or(false)
+            || processes.iter().any(|p| is_display_server(&p.name));
+
+        // Flag metrics known to be unreliable on this board/VBIOS
+        let warnings = device_info.known_issues();
+
+        // Get XID errors for this GPU
+        #[cfg(all(feature = "xid", target_os = "linux"))]
+        let recent_xids = self.get_recent_xids(&device_info.pci_bus_id);
+
+        Ok(GpuInfo {
+            device: device_info,
+            metrics,
+            memory,
+            remapped_rows,
+            retired_pages,
+            throttle_reasons,
+            warnings,
+            mig_instances,
+            driving_display,
+            processes,
+            process_count,
+            processes_collected: options.processes,
+            sampled_at,
+            #[cfg(all(feature = "xid", target_os = "linux"))]
+            recent_xids,
+        })
+    }
+
+    /// Read every temperature sensor of a device, keyed by sensor name
+    ///
+    /// Always contains "gpu". Other sensors come from the thermal settings
+    /// and, on HBM cards that report it only there, the memory temperature
+    /// field.
+    fn read_temperatures(
+        &self,
+        device: &nvml_wrapper::Device,
+        gpu: u32,
+        memory: Option<u32>,
+    ) -> BTreeMap<String, u32> {
+        let mut temperatures = BTreeMap::from([("gpu".to_string(), gpu)]);
+        let sensors = self.raw.as_ref().and_then(|raw| raw.thermal_sensors(device).ok()).unwrap_or_default();
+        for (target, current) in sensors {
+            if let (Some(name), Ok(current)) = (thermal_target_name(target), u32::try_from(current)) {
+                temperatures.entry(name.to_string()).or_insert(current);
+            }
+        }
+        if let Some(memory) = memory {
+            temperatures.entry("memory".to_string()).or_insert(memory);
+        }
+        temperatures
+    }
+
+    /// Get the MIG instances of a device with their GPU instance profiles
+    /// Get the supported clock range of a device, cached since it does not change at runtime
+    fn clock_range(&self, index: u32, device: &nvml_wrapper::Device) -> Option<ClockRange> {
+        if let Some(range) = self.clock_ranges.lock().ok().and_then(|c| c.get(&index).copied()) {
+            return range;
+        }
+        let range = get_clock_range(device);
+        if let Ok(mut cache) = self.clock_ranges.lock() {
+            cache.insert(index, range);
+        }
+        range
+    }
+
+    /// Get the MIG GPU instance profiles of a device, cached until the next reset
+    fn mig_profiles(&self, index: u32, raw: &RawNvml, device: &nvml_wrapper::Device) -> Vec<GpuInstanceProfile> {
+        use nvml_wrapper_sys::bindings::NVML_GPU_INSTANCE_PROFILE_COUNT;
+
+        if let Some(profiles) = self.mig_profiles.lock().ok().and_then(|c| c.get(&index).cloned()) {
+            return profiles;
+        }
+        let profiles: Vec<_> = (0..NVML_GPU_INSTANCE_PROFILE_COUNT)
+            .filter_map(|profile| raw.gpu_instance_profile(device, profile).ok())
+            .collect();
+        if let Ok(mut cache) = self.mig_profiles.lock() {
+            cache.insert(index, profiles.clone());
+        }
+        profiles
+    }
+
+    fn get_mig_instances(&self, index: u32, device: &nvml_wrapper::Device) -> Vec<MigInstance> {
+        let Some(raw) = self.raw.as_ref() else {
+            return Vec::new();
+        };
+        let handles = match raw.mig_device_handles(device) {
+            Ok(handles) => handles,
+            Err(e) => {
+                tracing::debug!("Failed to enumerate MIG devices: {}", e);
+                return Vec::new();
+            }
+        };
+        let profiles = self.mig_profiles(index, raw, device);
+
+        let mut instances: Vec<_> = handles
+            .into_iter()
+            .filter_map(|handle| {
+                // SAFETY: a MIG device handle returned by NVML for this library instance
+                let mig_device = unsafe { nvml_wrapper::Device::new(handle, &self.nvml) };
+                let (gpu_instance_id, compute_instance_id) = raw.mig_instance_ids(&mig_device).ok()?;
+                let profile_id = raw.gpu_instance_profile_id(device, gpu_instance_id).ok();
+                let profile = profile_id.and_then(|id| profiles.iter().find(|p| p.id == id));
+                Some(MigInstance {
+                    gpu_instance_id,
+                    compute_instance_id,
+                    profile: mig_profile_name(profile_id, profile),
+                    uuid: mig_device.uuid().unwrap_or_default(),
+                    memory_total: mig_device.memory_info().map(|m| m.total).unwrap_or(0),
+                })
+            })
+            .collect();
+        instances.sort_by_key(|i| (i.gpu_instance_id, i.compute_instance_id));
+        instances
+    }
+
+    /// Get the minimum P0 clock of a clock type (`NVML_CLOCK_*`) as its base clock
+    fn pstate_base_clock(&self, device: &nvml_wrapper::Device, clock_type: u32) -> Option<u32> {
+        use nvml_wrapper_sys::bindings::nvmlPStates_enum_NVML_PSTATE_0;
+
+        self.raw
+            .as_ref()?
+            .pstate_clock_range(device, clock_type, nvmlPStates_enum_NVML_PSTATE_0)
+            .ok()
+            .map(|(min, _)| min)
+    }
+
+    /// Get the activity of each process on a device, keyed by PID
+    ///
+    /// With `snapshot`, reads the utilization samples since the previous
+    /// snapshot of this device (the first covers all samples buffered by the
+    /// driver) and caches the result. Otherwise returns the cached result, so
+    /// ad-hoc queries do not consume samples a snapshot would miss. Returns
+    /// None if per-process utilization is not supported or nothing is cached.
+    fn process_activity(&self, device: &nvml_wrapper::Device, snapshot: bool) -> Option<HashMap<u32, ProcessActivity>> {
+        use nvml_wrapper::error::NvmlError;
+
+        let index = device.index().ok()?;
+        if !snapshot {
+            return self.process_activity.lock().ok()?.get(&index).cloned();
+        }
+        let mut seen = self.utilization_seen.lock().ok()?;
+
+        let samples = match device.process_utilization_stats(seen.get(&index).copied()) {
+            Ok(samples) => samples,
+            // No process was sampled since the last query
+            Err(NvmlError::NotFound) => Vec::new(),
+            Err(_) => return None,
+        };
+        if let Some(newest) = samples.iter().map(|s| s.timestamp).max() {
+            seen.insert(index, newest);
+        }
+
+        let mut activity: HashMap<u32, ProcessActivity> = HashMap::new();
+        for sample in &samples {
+            let entry = activity.entry(sample.pid).or_default();
+            entry.active |= sample.sm_util > 0 || sample.mem_util > 0 || sample.enc_util > 0 || sample.dec_util > 0;
+            entry.sm_utilization = entry.sm_utilization.max(sample.sm_util);
+        }
+        if let Ok(mut cache) = self.process_activity.lock() {
+            cache.insert(index, activity.clone());
+        }
+        Some(activity)
+    }
+
+    /// Get the name, owner and command line of a process, using the cache when possible
+    ///
+    /// Reads `/proc/{pid}` and falls back to NVML for the name if `/proc` is
+    /// unavailable (e.g., in minimal containers). A PID that cannot be resolved
+    /// while in a nested PID namespace is most likely a host process outside
+    /// it, and is named [`UNKNOWN_OTHER_NAMESPACE`].
+    fn process_identity(&self, pid: u32) -> ProcessIdentity {
+        if let Some(identity) = self
+            .process_identities
+            .lock()
+            .ok()
+            .and_then(|ids| ids.get(&pid).cloned())
+        {
+            return identity;
+        }
+
+        let name = get_process_name(pid).or_else(|| {
+            static PROC_UNAVAILABLE: Once = Once::new();
+            PROC_UNAVAILABLE.call_once(|| {
+                tracing::debug!("Cannot read /proc/{}/comm, falling back to NVML process names", pid);
+            });
+            self.nvml.sys_process_name(pid, 64).ok()
+        });
+
+        match name {
+            Some(name) => {
+                let identity = ProcessIdentity {
+                    name,
+                    user: get_process_owner(pid),
+                    cmdline: get_process_cmdline(pid),
+                    start_time: get_process_start_time(pid),
+                };
+                if let Ok(mut ids) = self.process_identities.lock() {
+                    ids.insert(pid, identity.clone());
+                }
+                identity
+            }
+            None => ProcessIdentity {
+                name: if self.nested_pid_namespace { UNKNOWN_OTHER_NAMESPACE } else { "unknown" }.to_string(),
+                user: "unknown".to_string(),
+                cmdline: None,
+                start_time: None,
+            },
+        }
+    }
+
+    /// Get recent XID events for the GPU with the given PCI bus ID
+    #[cfg(all(feature = "xid", target_os = "linux"))]
+    fn get_recent_xids(&self, pci_bus_id: &str) -> Vec<XidEvent> {
+        match self.xid.lock() {
+            Ok(mut watcher) => {
+                watcher.poll();
+                watcher.events_for(pci_bus_id)
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Get processes using a GPU device
+    ///
+    /// Returns the processes (limited to `max_processes`) and the total count.
+    /// Only a `snapshot` reads new utilization samples, see [`process_activity`](Self::process_activity).
+    fn get_gpu_processes(
+        &self,
+        device: &nvml_wrapper::Device,
+        snapshot: bool,
+    ) -> Result<(Vec<GpuProcess>, usize)> {
+        let mut processes = Vec::new();
+
+        // Get compute processes
+        if let Ok(compute_procs) = device.running_compute_processes() {
+            for proc in compute_procs {
+                let memory = extract_gpu_memory(proc.used_gpu_memory);
+                processes.push(GpuProcess {
+                    pid: proc.pid,
+                    name: String::new(),
+                    gpu_memory: memory,
+                    process_type: ProcessType::Compute,
+                    active: false,
+                    user: String::new(),
+                    cmdline: None,
+                    sm_utilization: None,
+                    runtime_seconds: None,
+                });
+            }
+        }
+
+        // Get graphics processes
+        if let Ok(graphics_procs) = device.running_graphics_processes() {
+            for proc in graphics_procs {
+                let memory = extract_gpu_memory(proc.used_gpu_memory);
+                // Check if we already have this process as compute
+                if let Some(existing) = processes.iter_mut().find(|p| p.pid == proc.pid) {
+                    existing.process_type = ProcessType::Mixed;
+                    existing.gpu_memory = existing.gpu_memory.max(memory);
+                } else {
+                    processes.push(GpuProcess {
+                        pid: proc.pid,
+                        name: String::new(),
+                        gpu_memory: memory,
+                        process_type: ProcessType::Graphics,
+                        active: false,
+                        user: String::new(),
+                        cmdline: None,
+                        sm_utilization: None,
+                        runtime_seconds: None,
+                    });
+                }
+            }
+        }
+
+        // Mark processes with GPU activity since the previous snapshot
+        if let Some(activity) = self.process_activity(device, snapshot) {
+            for proc in &mut processes {
+                let own = activity.get(&proc.pid).copied().unwrap_or_default();
+                proc.active = own.active;
+                proc.sm_utilization = Some(own.sm_utilization);
+            }
+        }
+
+        sort_processes(&mut processes);
+
+        // Keep only the top memory users, then resolve their names
+        let total = processes.len();
+        if let Some(max) = self.max_processes {
+            processes.truncate(max);
+        }
+        let uptime = get_system_uptime();
+        for proc in &mut processes {
+            let identity = self.process_identity(proc.pid);
+            proc.name = identity.name;
+            proc.user = identity.user;
+            proc.cmdline = identity.cmdline;
+            proc.runtime_seconds = uptime
+                .zip(identity.start_time)
+                .map(|(uptime, start)| uptime.saturating_sub(start));
+        }
+
+        Ok((processes, total))
+    }
+}
+
+/// Human-readable name of a GPU instance profile such as "3g.20gb"
+///
+/// Uses the driver's name when it reports one, otherwise derives it from the
+/// slice count and memory size, and falls back to the raw profile ID.
+fn mig_profile_name(profile_id: Option<u32>, profile: Option<&GpuInstanceProfile>) -> String {
+    match (profile, profile_id) {
+        (Some(GpuInstanceProfile { name: Some(name), .. }), _) => {
+            name.strip_prefix("MIG ").unwrap_or(name).to_string()
+        }
+        (Some(profile), _) => format!("{}g.{}gb", profile.slices, profile.memory_mb.div_ceil(1024)),
+        (None, Some(id)) => format!("profile {}", id),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+/// Sensor name of a thermal target (`NVML_THERMAL_TARGET_*`), None for unknown targets
+fn thermal_target_name(target: i32) -> Option<&'static str> {
+    match target {
+        1 => Some("gpu"),
+        2 => Some("memory"),
+        4 => Some("power_supply"),
+        8 => Some("board"),
+        9 => Some("vcd_board"),
+        10 => Some("vcd_inlet"),
+        11 => Some("vcd_outlet"),
+        _ => None,
+    }
+}
+
+/// Check if a process is an X server or Wayland compositor
+fn is_display_server(name: &str) -> bool {
+    DISPLAY_SERVERS.contains(&name)
+}
+
+/// Sort processes by memory usage (descending), then by PID
+///
+/// The PID tie-break keeps equal-memory processes in the same order across
+/// samples, since NVML does not list processes in a stable order.
+fn sort_processes(processes: &mut [GpuProcess]) {
+    processes.sort_by_key(|p| (std::cmp::Reverse(p.gpu_memory), p.pid));
+}
+
+/// Extract GPU memory value from UsedGpuMemory enum
+fn extract_gpu_memory(used: nvml_wrapper::enums::device::UsedGpuMemory) -> u64 {
+    use nvml_wrapper::enums::device::UsedGpuMemory;
+    match used {
+        UsedGpuMemory::Used(bytes) => bytes,
+        UsedGpuMemory::Unavailable => 0,
+    }
+}
+
+/// Get the supported clock range from the supported clocks lists
+fn get_clock_range(device: &nvml_wrapper::Device) -> Option<ClockRange> {
+    let memory_clocks = device.supported_memory_clocks().ok()?;
+    let memory_min = *memory_clocks.iter().min()?;
+    let memory_max = *memory_clocks.iter().max()?;
+
+    // Supported graphics clocks depend on the memory clock
+    let graphics_clocks: Vec<u32> = memory_clocks
+        .iter()
+        .filter_map(|&mem| device.supported_graphics_clocks(mem).ok())
+        .flatten()
+        .collect();
+    let graphics_min = *graphics_clocks.iter().min()?;
+    let graphics_max = *graphics_clocks.iter().max()?;
+
+    Some(ClockRange {
+        graphics_min,
+        graphics_max,
+        memory_min,
+        memory_max,
+    })
+}
+
+/// Get the current driver model (TCC/WDDM is only reported on Windows)
+#[cfg(windows)]
+fn get_driver_model(device: &nvml_wrapper::Device) -> Option<DriverModel> {
+    use nvml_wrapper::enum_wrappers::device::DriverModel as NvmlDriverModel;
+
+    device.driver_model().ok().map(|state| match state.current {
+        NvmlDriverModel::WDM => DriverModel::Tcc,
+        NvmlDriverModel::WDDM => DriverModel::Wddm,
+    })
+}
+
+/// Get the current driver model (TCC/WDDM is only reported on Windows)
+#[cfg(not(windows))]
+fn get_driver_model(_device: &nvml_wrapper::Device) -> Option<DriverModel> {
+    None
+}
+
+/// Estimate the memory taken by ECC check bits from the usable total
+///
+/// GDDR stores one check byte per 16 bytes in regular memory, leaving 15/16
+/// usable; HBM (buses of 1024 bits and wider) has dedicated ECC storage.
+fn ecc_reserved(total: u64, ecc_enabled: Option<bool>, bus_width: Option<u32>) -> Option<u64> {
+    if ecc_enabled != Some(true) {
+        return None;
+    }
+    match bus_width? {
+        width if width >= HBM_MIN_BUS_WIDTH => Some(0),
+        _ => Some(total / 15),
+    }
+}
+
+/// Get whether persistence mode is enabled (only supported on Linux)
+#[cfg(target_os = "linux")]
+fn get_persistence_mode(device: &nvml_wrapper::Device) -> Option<bool> {
+    device.is_in_persistent_mode().ok()
+}
+
+/// Get whether persistence mode is enabled (only supported on Linux)
+#[cfg(not(target_os = "linux"))]
+fn get_persistence_mode(_device: &nvml_wrapper::Device) -> Option<bool> {
+    None
+}
+
+/// Read retired page counts and the pending flag
+fn read_retired_pages(device: &nvml_wrapper::Device) -> Result<RetiredPages> {
+    use nvml_wrapper::enum_wrappers::device::RetirementCause;
+
+    Ok(RetiredPages {
+        single_bit_ecc: device
+            .retired_pages(RetirementCause::MultipleSingleBitEccErrors)?
+            .len() as u32,
+        double_bit_ecc: device.retired_pages(RetirementCause::DoubleBitEccError)?.len() as u32,
+        pending: device.are_pages_pending_retired()?,
+    })
+}
+
+/// Dynamic values fetched with one `nvmlDeviceGetFieldValues` call per sample
+///
+/// Replaces one driver call per value. NVML has no field IDs for utilization,
+/// the GPU temperature or clocks, so those are still read individually.
+struct SampleFields {
+    /// Instantaneous power usage in milliwatts, as from `nvmlDeviceGetPowerUsage`
+    power_usage: Option<u32>,
+    /// Memory temperature in Celsius, None on boards without a memory sensor
+    memory_temperature: Option<u32>,
+    pcie_replay_count: Option<u64>,
+    remapped_rows: Option<RemappedRows>,
+}
+
+impl SampleFields {
+    /// Field IDs in the order `from_values` expects them
+    const IDS: [u32; 7] = {
+        use nvml_wrapper::sys_exports::field_id::{
+            NVML_FI_DEV_MEMORY_TEMP, NVML_FI_DEV_PCIE_REPLAY_COUNTER, NVML_FI_DEV_POWER_INSTANT,
+            NVML_FI_DEV_REMAPPED_COR, NVML_FI_DEV_REMAPPED_FAILURE, NVML_FI_DEV_REMAPPED_PENDING,
+            NVML_FI_DEV_REMAPPED_UNC,
+        };
+        [
+            NVML_FI_DEV_POWER_INSTANT,
+            NVML_FI_DEV_MEMORY_TEMP,
+            NVML_FI_DEV_PCIE_REPLAY_COUNTER,
+            NVML_FI_DEV_REMAPPED_COR,
+            NVML_FI_DEV_REMAPPED_UNC,
+            NVML_FI_DEV_REMAPPED_PENDING,
+            NVML_FI_DEV_REMAPPED_FAILURE,
+        ]
+    };
+
+    /// Read all fields at once, falling back to individual calls if the batch call fails
+    fn read(device: &nvml_wrapper::Device) -> Self {
+        use nvml_wrapper::structs::device::FieldId;
+
+        let ids: Vec<FieldId> = Self::IDS.iter().map(|&id| FieldId(id)).collect();
+        let mut fields = match device.field_values_for(&ids) {
+            Ok(samples) => {
+                let values: Vec<Option<u64>> = samples
+                    .into_iter()
+                    .map(|sample| sample.ok().and_then(|s| s.value.ok()).map(sample_value_u64))
+                    .collect();
+                Self::from_values(&values)
+            }
+            Err(e) => {
+                tracing::debug!("Batched field values not supported, reading individually: {}", e);
+                Self {
+                    power_usage: None,
+                    memory_temperature: None,
+                    pcie_replay_count: device.pcie_replay_counter().ok().map(u64::from),
+                    remapped_rows: read_remapped_rows(device).ok(),
+                }
+            }
+        };
+        // The power field needs a recent driver and GPU; the dedicated call works everywhere
+        if fields.power_usage.is_none() {
+            fields.power_usage = device.power_usage().ok();
+        }
+        fields
+    }
+
+    /// Interpret field values in `IDS` order, None for fields that could not be read
+    fn from_values(values: &[Option<u64>]) -> Self {
+        let value = |i: usize| values.get(i).copied().flatten();
+        let remapped_rows = match (value(3), value(4), value(5), value(6)) {
+            (Some(correctable), Some(uncorrectable), Some(pending), Some(failure)) => Some(RemappedRows {
+                correctable: correctable as u32,
+                uncorrectable: uncorrectable as u32,
+                pending: pending != 0,
+                failure_occurred: failure != 0,
+            }),
+            _ => None,
+        };
+        Self {
+            power_usage: value(0).map(|mw| mw as u32),
+            // Boards without a memory sensor report 0 rather than an error
+            memory_temperature: value(1).filter(|&t| t > 0).map(|t| t as u32),
+            pcie_replay_count: value(2),
+            remapped_rows,
+        }
+    }
+}
+
+/// Read the statistics of every process in the accounting buffer, most recent first
+fn read_accounting(device: &nvml_wrapper::Device, index: u32) -> Result<Vec<AccountingRecord>> {
+    use nvml_wrapper::error::NvmlError;
+
+    if !device.is_accounting_enabled()? {
+        return Err(Error::AccountingDisabled(index));
+    }
+    let mut records = Vec::new();
+    for pid in device.accounting_pids()? {
+        let stats = match device.accounting_stats_for(pid) {
+            Ok(stats) => stats,
+            // Evicted from the buffer since the PIDs were listed
+            Err(NvmlError::NotFound) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        records.push(AccountingRecord {
+            pid,
+            is_running: stats.is_running,
+            start_time_us: stats.start_time,
+            run_time_ms: stats.time,
+            gpu_utilization: stats.gpu_utilization,
+            memory_utilization: stats.memory_utilization,
+            max_memory_usage: stats.max_memory_usage,
+        });
+    }
+    records.sort_by_key(|r| std::cmp::Reverse(r.start_time_us));
+    Ok(records)
+}
+
+/// Read remapped row counts and flags via NVML field values
+fn read_remapped_rows(device: &nvml_wrapper::Device) -> Result<RemappedRows> {
+    use nvml_wrapper::sys_exports::field_id::{
+        NVML_FI_DEV_REMAPPED_COR, NVML_FI_DEV_REMAPPED_FAILURE, NVML_FI_DEV_REMAPPED_PENDING,
+        NVML_FI_DEV_REMAPPED_UNC,
+    };
+
+    let values = read_field_values(
+        device,
+        &[
+            NVML_FI_DEV_REMAPPED_COR,
+            NVML_FI_DEV_REMAPPED_UNC,
+            NVML_FI_DEV_REMAPPED_PENDING,
+            NVML_FI_DEV_REMAPPED_FAILURE,
+        ],
+    )?;
+
+    Ok(RemappedRows {
+        correctable: values[0] as u32,
+        uncorrectable: values[1] as u32,
+        pending: values[2] != 0,
+        failure_occurred: values[3] != 0,
+    })
+}
+
+/// Read NVML field values as integers, failing if any field is unavailable
+fn read_field_values(device: &nvml_wrapper::Device, ids: &[u32]) -> Result<Vec<u64>> {
+    use nvml_wrapper::structs::device::FieldId;
+
+    let ids: Vec<FieldId> = ids.iter().map(|&id| FieldId(id)).collect();
+    device
+        .field_values_for(&ids)?
+        .into_iter()
+        .map(|sample| Ok(sample_value_u64(sample?.value?)))
+        .collect()
+}
+
+/// Convert an NVML sample value to an integer
+fn sample_value_u64(value: nvml_wrapper::enums::device::SampleValue) -> u64 {
+    use nvml_wrapper::enums::device::SampleValue;
+
+    match value {
+        SampleValue::F64(v) => v as u64,
+        SampleValue::U32(v) => v as u64,
+        SampleValue::U64(v) => v,
+        SampleValue::I64(v) => v as u64,
+    }
+}
+
+/// Get the NUMA node of a PCI device from /sys/bus/pci/devices/<address>/numa_node
+///
+/// The kernel reports -1 when the device has no NUMA affinity.
+fn get_numa_node(pci_bus_id: &str) -> Option<i32> {
+    let path = Path::new("/sys/bus/pci/devices")
+        .join(sysfs_pci_address(pci_bus_id))
+        .join("numa_node");
+    let node: i32 = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    (node >= 0).then_some(node)
+}
+
+/// Convert an NVML bus ID (`00000000:3B:00.0`) to a sysfs address (`0000:3b:00.0`)
+fn sysfs_pci_address(pci_bus_id: &str) -> String {
+    let address = pci_bus_id.to_ascii_lowercase();
+    // NVML pads the domain to 8 digits, sysfs uses 4
+    match address.split_once(':') {
+        Some((domain, rest)) if domain.len() > 4 => {
+            format!("{}:{}", &domain[domain.len() - 4..], rest)
+        }
+        _ => address,
+    }
+}
+
+/// Get the PCI bus IDs at the remote end of each active NVLink
+fn get_nvlink_peers(device: &nvml_wrapper::Device) -> Vec<String> {
+    (0..NVLINK_MAX_LINKS)
+        .map(|link| device.link_wrapper_for(link))
+        .filter(|link| link.is_active().unwrap_or(false))
+        .filter_map(|link| link.remote_pci_info().ok())
+        .map(|pci| pci.bus_id)
+        .collect()
+}
+
+/// Map a CUDA ordinal to an NVML index given `CUDA_VISIBLE_DEVICES` and the device UUIDs in NVML order
+fn resolve_cuda_ordinal(visible: Option<&str>, ordinal: u32, uuids: &[String]) -> Option<u32> {
+    let Some(visible) = visible else {
+        return (ordinal < uuids.len() as u32).then_some(ordinal);
+    };
+
+    let mut devices = Vec::new();
+    for entry in visible.split(',').map(str::trim) {
+        let index = if let Ok(index) = entry.parse::<u32>() {
+            (index < uuids.len() as u32).then_some(index)
+        } else if entry.starts_with("GPU-") {
+            let mut matches = (0..uuids.len() as u32).filter(|&i| uuids[i as usize].starts_with(entry));
+            match (matches.next(), matches.next()) {
+                (Some(index), None) => Some(index),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        match index {
+            Some(index) if !devices.contains(&index) => devices.push(index),
+            _ => break,
+        }
+    }
+    devices.get(ordinal as usize).copied()
+}
+
+/// Get the current time in milliseconds since the Unix epoch
+fn unix_millis_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// GPU activity of a process over the utilization samples of one snapshot
+#[derive(Debug, Clone, Copy, Default)]
+struct ProcessActivity {
+    /// Any engine was busy for the process
+    active: bool,
+    /// Highest SM utilization (0-100)
+    sm_utilization: u32,
+}
+
+/// Cached identity of a process
+#[derive(Debug, Clone)]
+struct ProcessIdentity {
+    name: String,
+    user: String,
+    cmdline: Option<String>,
+    /// Start time in seconds since boot
+    start_time: Option<u64>,
+}
+
+/// Check if this process runs in a nested PID namespace, from the `NSpid` line of `/proc/self/status`
+///
+/// False where `/proc` or the line (Linux 4.1+) is unavailable.
+fn in_nested_pid_namespace() -> bool {
+    fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| pid_namespace_depth(&status))
+        .is_some_and(|depth| depth > 1)
+}
+
+/// Number of PID namespace levels in a `/proc/{pid}/status` file: one PID per level in `NSpid`
+fn pid_namespace_depth(status: &str) -> Option<usize> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("NSpid:"))
+        .map(|pids| pids.split_whitespace().count())
+}
+
+/// Get the full command line of a process from /proc/{pid}/cmdline
+fn get_process_cmdline(pid: u32) -> Option<String> {
+    let cmdline_path = Path::new("/proc").join(pid.to_string()).join("cmdline");
+    let raw = fs::read(cmdline_path).ok()?;
+    let args: Vec<String> = raw
+        .split(|&b| b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    (!args.is_empty()).then(|| args.join(" "))
+}
+
+/// Get process name from PID by reading /proc/{pid}/comm
+fn get_process_name(pid: u32) -> Option<String> {
+    let comm_path = Path::new("/proc").join(pid.to_string()).join("comm");
+    fs::read_to_string(comm_path)
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+/// Get the start time of a process in seconds since boot from /proc/{pid}/stat
+#[cfg(unix)]
+fn get_process_start_time(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("stat")).ok()?;
+    // SAFETY: sysconf has no preconditions
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks_per_second <= 0 {
+        return None;
+    }
+    Some(parse_start_ticks(&stat)? / ticks_per_second as u64)
+}
+
+/// Process start times are not available without /proc
+#[cfg(not(unix))]
+fn get_process_start_time(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Parse the start time in clock ticks since boot (field 22) from a /proc/{pid}/stat line
+fn parse_start_ticks(stat: &str) -> Option<u64> {
+    // The command name (field 2) may contain spaces and parentheses, so count from its end
+    let fields = &stat[stat.rfind(')')? + 1..];
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// Get the system uptime in whole seconds from /proc/uptime
+fn get_system_uptime() -> Option<u64> {
+    let uptime = fs::read_to_string("/proc/uptime").ok()?;
+    let seconds: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+    Some(seconds as u64)
+}
+
+/// Get the user owning a process from `/proc/{pid}/status` and `/etc/passwd`
+///
+/// Falls back to the numeric UID, or "unknown" if the process is not visible.
+fn get_process_owner(pid: u32) -> String {
+    let status = Path::new("/proc").join(pid.to_string()).join("status");
+    let uid = fs::read_to_string(status).ok().and_then(|status| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("Uid:"))
+            .and_then(|ids| ids.split_whitespace().next())
+            .map(str::to_string)
+    });
+    let Some(uid) = uid else {
+        return "unknown".to_string();
+    };
+
+    fs::read_to_string("/etc/passwd")
+        .ok()
+        .and_then(|passwd| {
+            passwd.lines().find_map(|line| {
+                let mut parts = line.split(':');
+                let name = parts.next()?;
+                (parts.nth(1)? == uid).then(|| name.to_string())
+            })
+        })
+        .unwrap_or(uid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_info_calculations() {
+        let mem = MemoryInfo {
+            total: 8 * 1024 * 1024 * 1024, // 8 GB
+            used: 2 * 1024 * 1024 * 1024,  // 2 GB
+            free: 6 * 1024 * 1024 * 1024,  // 6 GB
+            reserved: None,
+            ecc_reserved: None,
+            bar1_total: None,
+            bar1_used: None,
+        };
+
+        assert_eq!(mem.total_mib(), 8192);
+        assert_eq!(mem.used_mib(), 2048);
+        assert_eq!(mem.free_mib(), 6144);
+        assert!((mem.usage_percent() - 25.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_memory_gb() {
+        let mem = MemoryInfo {
+            total: 8 * 1024 * 1024 * 1024, // 8 GB
+            used: 2 * 1024 * 1024 * 1024,  // 2 GB
+            free: 6 * 1024 * 1024 * 1024,  // 6 GB
+            reserved: None,
+            ecc_reserved: None,
+            bar1_total: None,
+            bar1_used: None,
+        };
+
+        assert!((mem.total_gb() - 8.59).abs() < 0.01);
+        assert!((mem.used_gb() - 2.15).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_process_memory_percent() {
+        let proc = GpuProcess {
+            pid: 1234,
+            name: "python".to_string(),
+            gpu_memory: 2 * 1024 * 1024 * 1024, // 2 GB
+            process_type: ProcessType::Compute,
+            active: true,
+            user: "alice".to_string(),
+            cmdline: None,
+            sm_utilization: Some(80),
+            runtime_seconds: None,
+        };
+
+        assert!((proc.gpu_memory_percent(8 * 1024 * 1024 * 1024) - 25.0).abs() < 0.01);
+        assert_eq!(proc.gpu_memory_percent(0), 0.0);
+    }
+
+    #[test]
+    fn test_ecc_reserved() {
+        const GIB: u64 = 1024 * 1024 * 1024;
+        // 16 GiB GDDR6 card reports 15 GiB with ECC on
+        assert_eq!(ecc_reserved(15 * GIB, Some(true), Some(256)), Some(GIB));
+        // HBM loses no capacity to ECC
+        assert_eq!(ecc_reserved(80 * GIB, Some(true), Some(5120)), Some(0));
+        assert_eq!(ecc_reserved(16 * GIB, Some(false), Some(256)), None);
+        assert_eq!(ecc_reserved(16 * GIB, None, Some(256)), None);
+        assert_eq!(ecc_reserved(15 * GIB, Some(true), None), None);
+    }
+
+    #[test]
+    fn test_clock_state() {
+        assert_eq!(ClockState::infer(Some(1200), Some(1410)), ClockState::ApplicationLocked);
+        assert_eq!(ClockState::infer(None, None), ClockState::Unknown);
+        assert_eq!(ClockState::infer(Some(1410), None), ClockState::Unknown);
+
+        // Default application clocks cap boost under load, setting the
+        // applications clocks throttle reason without any lock in place
+        let reasons = ThrottleReason::from_bits(0x2);
+        assert_eq!(reasons, [ThrottleReason::ApplicationsClocksSetting]);
+        assert_eq!(ClockState::infer(Some(1410), Some(1410)), ClockState::Auto);
+    }
+
+    #[test]
+    fn test_mig_profile_name() {
+        let profile = |name: Option<&str>| GpuInstanceProfile {
+            id: 9,
+            slices: 3,
+            memory_mb: 19968,
+            name: name.map(str::to_string),
+        };
+        assert_eq!(mig_profile_name(Some(9), Some(&profile(Some("MIG 3g.20gb")))), "3g.20gb");
+        assert_eq!(mig_profile_name(Some(9), Some(&profile(None))), "3g.20gb");
+        assert_eq!(mig_profile_name(Some(19), None), "profile 19");
+    }
+
+    #[test]
+    fn test_sample_fields() {
+        let fields = SampleFields::from_values(&[Some(250_000), Some(0), Some(3), Some(1), Some(0), Some(1), Some(0)]);
+        assert_eq!(fields.power_usage, Some(250_000));
+        assert_eq!(fields.memory_temperature, None);
+        assert_eq!(fields.pcie_replay_count, Some(3));
+        let rows = fields.remapped_rows.unwrap();
+        assert_eq!((rows.correctable, rows.pending, rows.failure_occurred), (1, true, false));
+
+        // Remapped rows need all four fields
+        let fields = SampleFields::from_values(&[None, Some(72), None, Some(1), None, Some(0), Some(0)]);
+        assert_eq!(fields.memory_temperature, Some(72));
+        assert!(fields.remapped_rows.is_none());
+    }
+
+    #[test]
+    fn test_pid_namespace_depth() {
+        assert_eq!(pid_namespace_depth("Name:\tbash\nNSpid:\t4242\nNSpgid:\t4242\n"), Some(1));
+        assert_eq!(pid_namespace_depth("Name:\tbash\nNSpid:\t4242\t7\n"), Some(2));
+        assert_eq!(pid_namespace_depth("Name:\tbash\n"), None);
+    }
+
+    #[test]
+    fn test_thermal_target_name() {
+        assert_eq!(thermal_target_name(2), Some("memory"));
+        assert_eq!(thermal_target_name(10), Some("vcd_inlet"));
+        assert_eq!(thermal_target_name(-1), None);
+        assert_eq!(thermal_target_name(15), None);
+    }
+
+    #[test]
+    fn test_builder() {
+        let builder = GpuMonitor::builder();
+        assert_eq!(builder.min_interval, Duration::from_millis(100));
+        assert_eq!(builder.max_processes, None);
+        assert!(builder.options.processes);
+
+        let builder = builder.max_processes(5).skip_processes(true).max_retries(0);
+        assert_eq!(builder.max_processes, Some(5));
+        assert_eq!(builder.max_retries, 0);
+        assert!(!builder.options.processes);
+    }
+
+    #[test]
+    fn test_is_display_server() {
+        assert!(is_display_server("Xorg"));
+        assert!(is_display_server("gnome-shell"));
+        assert!(!is_display_server("python"));
+        assert!(!is_display_server("Xorg-helper"));
+    }
+
+    #[test]
+    fn test_sort_processes() {
+        let proc = |pid, gpu_memory| GpuProcess { pid, gpu_memory, ..Default::default() };
+        let mut processes = vec![proc(30, 100), proc(20, 500), proc(10, 100)];
+        sort_processes(&mut processes);
+
+        let pids: Vec<_> = processes.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, [20, 10, 30]);
+    }
+
+    #[test]
+    fn test_throttle_diagnostic() {
+        let metrics = GpuMetrics {
+            gpu_utilization: 100,
+            memory_utilization: 50,
+            encoder_utilization: 0,
+            decoder_utilization: 0,
+            encoder_sampling_us: None,
+            decoder_sampling_us: None,
+            ofa_utilization: None,
+            jpeg_utilization: None,
+            temperature: 80,
+            power_usage: 300_000,
+            fan_speed: None,
+            fan_speeds: Vec::new(),
+            clock_graphics: 1500,
+            clock_memory: 9500,
+            clock_sm: 1500,
+            pcie_replay_count: None,
+            temperatures: BTreeMap::new(),
+        };
+
+        let reasons = ThrottleReason::from_bits(0x4 | 0x20);
+        assert_eq!(reasons, vec![ThrottleReason::SwPowerCap, ThrottleReason::SwThermalSlowdown]);
+        assert_eq!(
+            metrics.throttle_diagnostic(1950, &reasons).as_deref(),
+            Some("clocks reduced by 450 MHz due to power cap, software thermal slowdown")
+        );
+
+        // Idle downclocking and running at max are not throttling
+        assert_eq!(metrics.throttle_diagnostic(1950, &[ThrottleReason::GpuIdle]), None);
+        assert_eq!(metrics.throttle_diagnostic(1500, &reasons), None);
+
+        // Memory past its limit is named, a cool or unknown memory is not
+        let mut reasons = ThrottleReason::from_bits(0x20);
+        ThrottleReason::classify_memory_thermal(&mut reasons, Some(96), Some(95));
+        assert_eq!(reasons, vec![ThrottleReason::SwThermalSlowdown, ThrottleReason::MemoryThermalSlowdown]);
+        ThrottleReason::classify_memory_thermal(&mut reasons, Some(96), Some(95));
+        assert_eq!(reasons.len(), 2);
+        let mut reasons = ThrottleReason::from_bits(0x20);
+        ThrottleReason::classify_memory_thermal(&mut reasons, Some(80), Some(95));
+        ThrottleReason::classify_memory_thermal(&mut reasons, Some(96), None);
+        assert_eq!(reasons, vec![ThrottleReason::SwThermalSlowdown]);
+    }
+
+    #[test]
+    fn test_resolve_cuda_ordinal() {
+        let uuids: Vec<String> = ["GPU-aaaa-1111", "GPU-bbbb-2222", "GPU-bbbc-3333"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        // Without the variable CUDA and NVML agree
+        assert_eq!(resolve_cuda_ordinal(None, 2, &uuids), Some(2));
+        assert_eq!(resolve_cuda_ordinal(None, 3, &uuids), None);
+
+        // Indices and UUID prefixes, in the order given
+        assert_eq!(resolve_cuda_ordinal(Some("2,0"), 0, &uuids), Some(2));
+        assert_eq!(resolve_cuda_ordinal(Some("GPU-bbbc, 0"), 1, &uuids), Some(0));
+
+        // The list ends at an ambiguous, unknown or repeated entry
+        assert_eq!(resolve_cuda_ordinal(Some("1,GPU-bbb,0"), 1, &uuids), None);
+        assert_eq!(resolve_cuda_ordinal(Some("0,7,1"), 1, &uuids), None);
+        assert_eq!(resolve_cuda_ordinal(Some("1,1"), 1, &uuids), None);
+        assert_eq!(resolve_cuda_ordinal(Some(""), 0, &uuids), None);
+    }
+
+    #[test]
+    fn test_parse_start_ticks() {
+        let stat = "4242 (python (worker)) S 1 4242 4242 0 -1 4194304 1 0 0 0 \
+                    5 2 0 0 20 0 8 0 123456 1000 100";
+        assert_eq!(parse_start_ticks(stat), Some(123456));
+        assert_eq!(parse_start_ticks("4242 (python) S 1"), None);
+    }
+
+    #[test]
+    fn test_thermal_margin() {
+        let metrics = GpuMetrics {
+            gpu_utilization: 0,
+            memory_utilization: 0,
+            encoder_utilization: 0,
+            decoder_utilization: 0,
+            encoder_sampling_us: None,
+            decoder_sampling_us: None,
+            ofa_utilization: None,
+            jpeg_utilization: None,
+            temperature: 82,
+            power_usage: 0,
+            fan_speed: None,
+            fan_speeds: Vec::new(),
+            clock_graphics: 0,
+            clock_memory: 0,
+            clock_sm: 0,
+            pcie_replay_count: None,
+            temperatures: BTreeMap::new(),
+        };
+        assert_eq!(metrics.thermal_margin(90), 8);
+        assert_eq!(metrics.thermal_margin(80), -2);
+    }
+
+    #[test]
+    fn test_sysfs_pci_address() {
+        assert_eq!(sysfs_pci_address("00000000:3B:00.0"), "0000:3b:00.0");
+        assert_eq!(sysfs_pci_address("0000:01:00.0"), "0000:01:00.0");
+    }
+
+    #[test]
+    fn test_cpu_ranges() {
+        use crate::topology::cpu_ranges;
+
+        // Cores 0-3 and 8 in the first word, core 64 in the second
+        let ranges = cpu_ranges(&[0b1_0000_1111, 0b1], 64);
+        let formatted: Vec<String> = ranges.iter().map(|r| r.to_string()).collect();
+        assert_eq!(formatted, ["0-3", "8", "64"]);
+
+        // A range spanning a word boundary stays contiguous
+        let ranges = cpu_ranges(&[1 << 31, 1], 32);
+        assert_eq!(ranges, [CpuRange { first: 31, last: 32 }]);
+    }
+
+    #[test]
+    fn test_temperature_status() {
+        let cool = GpuMetrics {
+            gpu_utilization: 0,
+            memory_utilization: 0,
+            encoder_utilization: 0,
+            decoder_utilization: 0,
+            encoder_sampling_us: None,
+            decoder_sampling_us: None,
+            ofa_utilization: None,
+            jpeg_utilization: None,
+            temperature: 40,
+            power_usage: 0,
+            fan_speed: None,
+            fan_speeds: Vec::new(),
+            clock_graphics: 0,
+            clock_memory: 0,
+            clock_sm: 0,
+            pcie_replay_count: None,
+            temperatures: BTreeMap::new(),
+        };
+        assert_eq!(cool.temperature_status(), crate::metrics::TemperatureStatus::Cool);
+
+        let hot = GpuMetrics {
+            temperature: 90,
+            ..cool.clone()
+        };
+        assert_eq!(hot.temperature_status(), crate::metrics::TemperatureStatus::Hot);
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        use nvml_wrapper::error::NvmlError;
+
+        assert!(Error::Nvml(NvmlError::Unknown).is_transient());
+        assert!(Error::Nvml(NvmlError::Timeout).is_transient());
+        assert!(!Error::Nvml(NvmlError::NotSupported).is_transient());
+        assert!(!Error::NoDevices.is_transient());
+
+        let error = Error::Nvml(NvmlError::Timeout).on_device(3);
+        assert!(error.is_transient());
+        assert_eq!(error.to_string(), format!("GPU 3: NVML error: {}", NvmlError::Timeout));
+        assert!(matches!(Error::NoDevices.on_device(3), Error::NoDevices));
+    }
+}
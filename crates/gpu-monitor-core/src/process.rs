@@ -1,59 +1,224 @@
-//! GPU process information
-
-use serde::{Deserialize, Serialize};
-
-/// Information about a process using the GPU
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GpuProcess {
-    /// Process ID
-    pub pid: u32,
-    /// Process name (executable name)
-    pub name: String,
-    /// GPU memory used by this process in bytes
-    pub gpu_memory: u64,
-    /// Process type
-    pub process_type: ProcessType,
-}
-
-impl GpuProcess {
-    /// Get GPU memory usage in MiB
-    pub fn gpu_memory_mib(&self) -> u64 {
-        self.gpu_memory / (1024 * 1024)
-    }
-}
-
-/// Type of GPU process
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum ProcessType {
-    /// Graphics/rendering process
-    Graphics,
-    /// Compute process (CUDA, OpenCL)
-    Compute,
-    /// Both graphics and compute
-    Mixed,
-    /// Unknown process type
-    Unknown,
-}
-
-impl std::fmt::Display for ProcessType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Graphics => write!(f, "Graphics"),
-            Self::Compute => write!(f, "Compute"),
-            Self::Mixed => write!(f, "Mixed"),
-            Self::Unknown => write!(f, "Unknown"),
-        }
-    }
-}
-
-impl ProcessType {
-    /// Short label for UI display
-    pub fn short_label(&self) -> &'static str {
-        match self {
-            Self::Graphics => "Gfx",
-            Self::Compute => "Comp",
-            Self::Mixed => "Mix",
-            Self::Unknown => "?",
-        }
-    }
-}
+//! GPU process information
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Name of a process that could not be resolved because it runs outside this PID namespace
+///
+/// Seen in containers: NVML reports host PIDs, which the container's `/proc`
+/// does not show. See [`GpuMonitor::nested_pid_namespace`](crate::GpuMonitor::nested_pid_namespace).
+pub const UNKNOWN_OTHER_NAMESPACE: &str = "unknown (other namespace)";
+
+/// Information about a process using the GPU
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GpuProcess {
+    /// Process ID
+    pub pid: u32,
+    /// Process name (executable name)
+    pub name: String,
+    /// User owning the process, numeric UID if it cannot be resolved
+    pub user: String,
+    /// Full command line, None if not readable
+    pub cmdline: Option<String>,
+    /// GPU memory used by this process in bytes
+    pub gpu_memory: u64,
+    /// Process type
+    pub process_type: ProcessType,
+    /// Process had nonzero GPU utilization since the previous sample
+    ///
+    /// False for processes that only hold memory (e.g. an idle notebook)
+    /// or when per-process utilization is not supported.
+    pub active: bool,
+    /// Highest SM utilization (0-100) since the previous sample, None if not supported
+    pub sm_utilization: Option<u32>,
+    /// Seconds since the process started, None if not readable
+    pub runtime_seconds: Option<u64>,
+}
+
+impl GpuProcess {
+    /// Get GPU memory usage in MiB
+    pub fn gpu_memory_mib(&self) -> u64 {
+        self.gpu_memory / (1024 * 1024)
+    }
+
+    /// Get GPU memory usage as percentage (0-100) of the given total memory in bytes
+    pub fn gpu_memory_percent(&self, total: u64) -> f32 {
+        if total == 0 {
+            0.0
+        } else {
+            (self.gpu_memory as f32 / total as f32) * 100.0
+        }
+    }
+}
+
+/// Statistics of a process recorded in a GPU's accounting buffer
+///
+/// The driver keeps these after the process exits, so they give a short
+/// history of recent jobs. Requires accounting mode (`nvidia-smi -am 1`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountingRecord {
+    /// Process ID
+    pub pid: u32,
+    /// Process is still running
+    pub is_running: bool,
+    /// Start time in microseconds since the Unix epoch
+    pub start_time_us: u64,
+    /// Milliseconds the process had a compute context, 0 while it is still running
+    pub run_time_ms: u64,
+    /// Percent (0-100) of the process lifetime with a kernel executing, None if not supported
+    pub gpu_utilization: Option<u32>,
+    /// Percent (0-100) of the process lifetime with device memory being read or written, None if not supported
+    pub memory_utilization: Option<u32>,
+    /// Peak GPU memory allocated by the process in bytes, None if not supported
+    pub max_memory_usage: Option<u64>,
+}
+
+/// Terminate a process (SIGTERM on Unix, `taskkill /F` on Windows)
+#[cfg(unix)]
+pub(crate) fn kill_process(pid: u32) -> Result<()> {
+    let pid_t = libc::pid_t::try_from(pid).map_err(|_| Error::ProcessNotFound(pid))?;
+    // SAFETY: kill has no memory safety requirements
+    if unsafe { libc::kill(pid_t, libc::SIGTERM) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::EPERM) => Err(Error::PermissionDenied(pid)),
+        Some(libc::ESRCH) => Err(Error::ProcessNotFound(pid)),
+        _ => Err(err.into()),
+    }
+}
+
+/// Terminate a process (SIGTERM on Unix, `taskkill /F` on Windows)
+#[cfg(windows)]
+pub(crate) fn kill_process(pid: u32) -> Result<()> {
+    let output = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("Access is denied") {
+        Err(Error::PermissionDenied(pid))
+    } else {
+        Err(Error::ProcessNotFound(pid))
+    }
+}
+
+/// GPU memory of a device's processes grouped by owner, ready for a treemap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessMemoryTree {
+    /// Device index (0-based)
+    pub gpu_index: u32,
+    /// GPU memory used by all processes in bytes
+    pub gpu_memory: u64,
+    /// Owners sorted by GPU memory (descending)
+    pub users: Vec<UserMemory>,
+}
+
+/// GPU memory used by the processes of one user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserMemory {
+    /// User name, or numeric UID if it cannot be resolved
+    pub user: String,
+    /// GPU memory used by this user's processes in bytes
+    pub gpu_memory: u64,
+    /// Processes sorted by GPU memory (descending)
+    pub processes: Vec<GpuProcess>,
+}
+
+impl ProcessMemoryTree {
+    /// Group processes tagged with their owner
+    pub fn from_owned(gpu_index: u32, processes: Vec<(String, GpuProcess)>) -> Self {
+        let mut users: Vec<UserMemory> = Vec::new();
+        for (user, process) in processes {
+            let node = match users.iter_mut().position(|u| u.user == user) {
+                Some(i) => &mut users[i],
+                None => {
+                    users.push(UserMemory {
+                        user,
+                        gpu_memory: 0,
+                        processes: Vec::new(),
+                    });
+                    users.last_mut().expect("just pushed")
+                }
+            };
+            node.gpu_memory += process.gpu_memory;
+            node.processes.push(process);
+        }
+
+        for node in &mut users {
+            node.processes.sort_by_key(|p| std::cmp::Reverse(p.gpu_memory));
+        }
+        users.sort_by(|a, b| b.gpu_memory.cmp(&a.gpu_memory).then_with(|| a.user.cmp(&b.user)));
+
+        Self {
+            gpu_index,
+            gpu_memory: users.iter().map(|u| u.gpu_memory).sum(),
+            users,
+        }
+    }
+}
+
+/// Type of GPU process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessType {
+    /// Graphics/rendering process
+    Graphics,
+    /// Compute process (CUDA, OpenCL)
+    Compute,
+    /// Both graphics and compute
+    Mixed,
+    /// Unknown process type
+    #[default]
+    Unknown,
+}
+
+impl std::fmt::Display for ProcessType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Graphics => write!(f, "Graphics"),
+            Self::Compute => write!(f, "Compute"),
+            Self::Mixed => write!(f, "Mixed"),
+            Self::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+impl ProcessType {
+    /// Short label for UI display
+    pub fn short_label(&self) -> &'static str {
+        match self {
+            Self::Graphics => "Gfx",
+            Self::Compute => "Comp",
+            Self::Mixed => "Mix",
+            Self::Unknown => "?",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_memory_tree() {
+        let proc = |pid, gpu_memory| GpuProcess { pid, gpu_memory, ..Default::default() };
+        let tree = ProcessMemoryTree::from_owned(
+            0,
+            vec![
+                ("alice".to_string(), proc(1, 100)),
+                ("bob".to_string(), proc(2, 300)),
+                ("alice".to_string(), proc(3, 250)),
+            ],
+        );
+
+        assert_eq!(tree.gpu_memory, 650);
+        assert_eq!(tree.users[0].user, "alice");
+        assert_eq!(tree.users[0].gpu_memory, 350);
+        assert_eq!(tree.users[0].processes[0].pid, 3);
+        assert_eq!(tree.users[1].user, "bob");
+    }
+}
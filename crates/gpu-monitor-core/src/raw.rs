@@ -0,0 +1,223 @@
+//! Direct NVML calls not wrapped by nvml-wrapper
+//!
+//! Loads its own handle to the NVML library and calls functions by symbol,
+//! reusing the device handles and error conversion from nvml-wrapper.
+
+use nvml_wrapper::error::{nvml_sym, nvml_try, NvmlError};
+use nvml_wrapper::Device;
+use nvml_wrapper_sys::bindings::{
+    nvmlDevice_t, nvmlGpuInstanceInfo_t, nvmlGpuInstanceProfileInfo_t, nvmlGpuInstanceProfileInfo_v2_t,
+    nvmlGpuInstancePlacement_t, nvmlGpuThermalSettings_t, nvmlMemory_v2_t, NvmlLib,
+};
+use std::ffi::CStr;
+
+/// NVML library path, matching the one used by nvml-wrapper
+#[cfg(windows)]
+const LIB_PATH: &str = "nvml.dll";
+
+/// NVML library path, matching the one used by nvml-wrapper
+#[cfg(not(windows))]
+const LIB_PATH: &str = "libnvidia-ml.so";
+
+/// A MIG GPU instance profile (`NVML_GPU_INSTANCE_PROFILE_*`)
+#[derive(Clone)]
+pub(crate) struct GpuInstanceProfile {
+    /// Profile ID as referenced by GPU instances
+    pub id: u32,
+    /// Number of GPU slices
+    pub slices: u32,
+    /// Memory in MiB
+    pub memory_mb: u64,
+    /// Driver-provided name such as "MIG 1g.5gb", None on drivers without v2 profile info
+    pub name: Option<String>,
+}
+
+/// Raw NVML library handle
+pub(crate) struct RawNvml {
+    lib: NvmlLib,
+}
+
+impl RawNvml {
+    /// Load the NVML library
+    ///
+    /// Must be called after `Nvml::init` so the library is initialized.
+    pub fn load() -> Option<Self> {
+        // SAFETY: loading the same library nvml-wrapper already loaded
+        unsafe { NvmlLib::new(LIB_PATH) }
+            .map(|lib| Self { lib })
+            .map_err(|e| tracing::debug!("Failed to load raw NVML symbols: {}", e))
+            .ok()
+    }
+
+    /// Get the fan control policy (`NVML_FAN_POLICY_*`) for a fan
+    pub fn fan_control_policy(&self, device: &Device, fan: u32) -> Result<u32, NvmlError> {
+        let sym = nvml_sym(self.lib.nvmlDeviceGetFanControlPolicy_v2.as_ref())?;
+        let mut policy = 0;
+        // SAFETY: valid device handle and out-pointer
+        unsafe { nvml_try(sym(device.handle(), fan, &mut policy))? };
+        Ok(policy)
+    }
+
+    /// Get memory info including memory reserved by the driver
+    pub fn memory_info_v2(&self, device: &Device) -> Result<nvmlMemory_v2_t, NvmlError> {
+        let sym = nvml_sym(self.lib.nvmlDeviceGetMemoryInfo_v2.as_ref())?;
+        let mut memory = nvmlMemory_v2_t {
+            // NVML_STRUCT_VERSION(Memory, 2)
+            version: std::mem::size_of::<nvmlMemory_v2_t>() as u32 | (2 << 24),
+            total: 0,
+            reserved: 0,
+            free: 0,
+            used: 0,
+        };
+        // SAFETY: valid device handle and versioned out-struct
+        unsafe { nvml_try(sym(device.handle(), &mut memory))? };
+        Ok(memory)
+    }
+
+    /// Get the current and pending MIG mode (`NVML_DEVICE_MIG_*`)
+    pub fn mig_mode(&self, device: &Device) -> Result<(u32, u32), NvmlError> {
+        let sym = nvml_sym(self.lib.nvmlDeviceGetMigMode.as_ref())?;
+        let mut current = 0;
+        let mut pending = 0;
+        // SAFETY: valid device handle and out-pointers
+        unsafe { nvml_try(sym(device.handle(), &mut current, &mut pending))? };
+        Ok((current, pending))
+    }
+
+    /// Get JPEG decoder utilization percentage (Ampere and later)
+    pub fn jpeg_utilization(&self, device: &Device) -> Result<u32, NvmlError> {
+        let sym = nvml_sym(self.lib.nvmlDeviceGetJpgUtilization.as_ref())?;
+        let mut utilization = 0;
+        let mut sampling_period_us = 0;
+        // SAFETY: valid device handle and out-pointers
+        unsafe { nvml_try(sym(device.handle(), &mut utilization, &mut sampling_period_us))? };
+        Ok(utilization)
+    }
+
+    /// Get optical flow accelerator utilization percentage (Turing and later)
+    pub fn ofa_utilization(&self, device: &Device) -> Result<u32, NvmlError> {
+        let sym = nvml_sym(self.lib.nvmlDeviceGetOfaUtilization.as_ref())?;
+        let mut utilization = 0;
+        let mut sampling_period_us = 0;
+        // SAFETY: valid device handle and out-pointers
+        unsafe { nvml_try(sym(device.handle(), &mut utilization, &mut sampling_period_us))? };
+        Ok(utilization)
+    }
+
+    /// Get the current temperature in Celsius of every thermal sensor, with its target (`NVML_THERMAL_TARGET_*`)
+    pub fn thermal_sensors(&self, device: &Device) -> Result<Vec<(i32, i32)>, NvmlError> {
+        let sym = nvml_sym(self.lib.nvmlDeviceGetThermalSettings.as_ref())?;
+        // SAFETY: plain C struct, all-zero is a valid value
+        let mut settings: nvmlGpuThermalSettings_t = unsafe { std::mem::zeroed() };
+        // SAFETY: valid device handle and out-struct; NVML_THERMAL_TARGET_ALL (15) selects every sensor
+        unsafe { nvml_try(sym(device.handle(), 15, &mut settings))? };
+        Ok(settings
+            .sensor
+            .iter()
+            .take(settings.count as usize)
+            .map(|sensor| (sensor.target, sensor.currentTemp))
+            .collect())
+    }
+
+    /// Get the min and max clock in MHz of a clock type (`NVML_CLOCK_*`) in a P-state
+    pub fn pstate_clock_range(
+        &self,
+        device: &Device,
+        clock_type: u32,
+        pstate: u32,
+    ) -> Result<(u32, u32), NvmlError> {
+        let sym = nvml_sym(self.lib.nvmlDeviceGetMinMaxClockOfPState.as_ref())?;
+        let mut min = 0;
+        let mut max = 0;
+        // SAFETY: valid device handle and out-pointers
+        unsafe { nvml_try(sym(device.handle(), clock_type, pstate, &mut min, &mut max))? };
+        Ok((min, max))
+    }
+
+    /// Get the handles of the MIG devices of a GPU, skipping empty slots
+    pub fn mig_device_handles(&self, device: &Device) -> Result<Vec<nvmlDevice_t>, NvmlError> {
+        let count_sym = nvml_sym(self.lib.nvmlDeviceGetMaxMigDeviceCount.as_ref())?;
+        let handle_sym = nvml_sym(self.lib.nvmlDeviceGetMigDeviceHandleByIndex.as_ref())?;
+        let mut count = 0;
+        // SAFETY: valid device handle and out-pointer
+        unsafe { nvml_try(count_sym(device.handle(), &mut count))? };
+
+        let mut handles = Vec::new();
+        for index in 0..count {
+            let mut handle = std::ptr::null_mut();
+            // SAFETY: valid device handle and out-pointer
+            match unsafe { nvml_try(handle_sym(device.handle(), index, &mut handle)) } {
+                Ok(()) => handles.push(handle),
+                Err(NvmlError::NotFound) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(handles)
+    }
+
+    /// Get the GPU instance and compute instance IDs of a MIG device
+    pub fn mig_instance_ids(&self, mig_device: &Device) -> Result<(u32, u32), NvmlError> {
+        let gpu_sym = nvml_sym(self.lib.nvmlDeviceGetGpuInstanceId.as_ref())?;
+        let compute_sym = nvml_sym(self.lib.nvmlDeviceGetComputeInstanceId.as_ref())?;
+        let mut gpu_instance = 0;
+        let mut compute_instance = 0;
+        // SAFETY: valid MIG device handle and out-pointers
+        unsafe {
+            nvml_try(gpu_sym(mig_device.handle(), &mut gpu_instance))?;
+            nvml_try(compute_sym(mig_device.handle(), &mut compute_instance))?;
+        }
+        Ok((gpu_instance, compute_instance))
+    }
+
+    /// Get the profile ID of a GPU instance
+    pub fn gpu_instance_profile_id(&self, device: &Device, gpu_instance: u32) -> Result<u32, NvmlError> {
+        let by_id_sym = nvml_sym(self.lib.nvmlDeviceGetGpuInstanceById.as_ref())?;
+        let info_sym = nvml_sym(self.lib.nvmlGpuInstanceGetInfo.as_ref())?;
+        let mut handle = std::ptr::null_mut();
+        let mut info = nvmlGpuInstanceInfo_t {
+            device: std::ptr::null_mut(),
+            id: 0,
+            profileId: 0,
+            placement: nvmlGpuInstancePlacement_t { start: 0, size: 0 },
+        };
+        // SAFETY: valid device handle, the GPU instance handle NVML returned and out-pointers
+        unsafe {
+            nvml_try(by_id_sym(device.handle(), gpu_instance, &mut handle))?;
+            nvml_try(info_sym(handle, &mut info))?;
+        }
+        Ok(info.profileId)
+    }
+
+    /// Get a GPU instance profile (`NVML_GPU_INSTANCE_PROFILE_*`), with its name if the driver reports one
+    pub fn gpu_instance_profile(&self, device: &Device, profile: u32) -> Result<GpuInstanceProfile, NvmlError> {
+        if let Ok(sym) = nvml_sym(self.lib.nvmlDeviceGetGpuInstanceProfileInfoV.as_ref()) {
+            // SAFETY: all-zero is a valid value for this plain C struct
+            let mut info: nvmlGpuInstanceProfileInfo_v2_t = unsafe { std::mem::zeroed() };
+            // NVML_STRUCT_VERSION(GpuInstanceProfileInfo, 2)
+            info.version = std::mem::size_of::<nvmlGpuInstanceProfileInfo_v2_t>() as u32 | (2 << 24);
+            // SAFETY: valid device handle and versioned out-struct
+            if unsafe { nvml_try(sym(device.handle(), profile, &mut info)) }.is_ok() {
+                // SAFETY: NVML writes a NUL-terminated name into the buffer
+                let name = unsafe { CStr::from_ptr(info.name.as_ptr()) }.to_string_lossy().into_owned();
+                return Ok(GpuInstanceProfile {
+                    id: info.id,
+                    slices: info.sliceCount,
+                    memory_mb: info.memorySizeMB,
+                    name: (!name.is_empty()).then_some(name),
+                });
+            }
+        }
+
+        let sym = nvml_sym(self.lib.nvmlDeviceGetGpuInstanceProfileInfo.as_ref())?;
+        // SAFETY: all-zero is a valid value for this plain C struct
+        let mut info: nvmlGpuInstanceProfileInfo_t = unsafe { std::mem::zeroed() };
+        // SAFETY: valid device handle and out-struct
+        unsafe { nvml_try(sym(device.handle(), profile, &mut info))? };
+        Ok(GpuInstanceProfile {
+            id: info.id,
+            slices: info.sliceCount,
+            memory_mb: info.memorySizeMB,
+            name: None,
+        })
+    }
+}
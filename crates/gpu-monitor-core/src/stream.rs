@@ -0,0 +1,92 @@
+//! Pull-based sampling stream
+
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::monitor::GpuMonitor;
+use crate::GpuInfo;
+
+/// All GPUs sampled at one point in time
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Snapshot {
+    /// Sample timestamp in milliseconds since the Unix epoch
+    pub sampled_at: u64,
+    /// Information for every GPU
+    pub gpus: Vec<GpuInfo>,
+    /// How long the NVML query took; near zero if a cached sample was returned
+    pub latency: Duration,
+}
+
+/// Iterator yielding a [`Snapshot`] per interval, created by [`GpuMonitor::samples`]
+///
+/// Samples are taken on fixed ticks from the first call, so time spent
+/// sampling or in the consumer does not make the stream drift. Ticks missed
+/// by a slow consumer are skipped rather than sampled back to back.
+pub struct Samples<'a> {
+    monitor: &'a GpuMonitor,
+    interval: Duration,
+    /// When the next sample is due, None before the first sample
+    next_tick: Option<Instant>,
+}
+
+impl<'a> Samples<'a> {
+    pub(crate) fn new(monitor: &'a GpuMonitor, interval: Duration) -> Self {
+        Self {
+            monitor,
+            interval,
+            next_tick: None,
+        }
+    }
+}
+
+impl Iterator for Samples<'_> {
+    type Item = Result<Snapshot>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tick = match self.next_tick {
+            Some(tick) => {
+                std::thread::sleep(tick.saturating_duration_since(Instant::now()));
+                tick
+            }
+            None => Instant::now(),
+        };
+        self.next_tick = Some(next_tick(tick, self.interval, Instant::now()));
+
+        let started = Instant::now();
+        Some(self.monitor.get_all_gpu_info().map(|gpus| Snapshot {
+            sampled_at: gpus.first().map(|g| g.sampled_at).unwrap_or_default(),
+            gpus,
+            latency: started.elapsed(),
+        }))
+    }
+}
+
+/// First tick after `tick` on the `interval` grid that is not already in the past
+fn next_tick(tick: Instant, interval: Duration, now: Instant) -> Instant {
+    let next = tick + interval;
+    if next >= now || interval.is_zero() {
+        return next;
+    }
+    let behind = now.duration_since(next).as_nanos() / interval.as_nanos();
+    next + interval * (behind as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_tick() {
+        let start = Instant::now();
+        let interval = Duration::from_millis(100);
+
+        // On time: the next tick is one interval later, regardless of sampling cost
+        assert_eq!(next_tick(start, interval, start + Duration::from_millis(30)), start + interval);
+
+        // Late by more than two intervals: missed ticks are skipped, staying on the grid
+        assert_eq!(
+            next_tick(start, interval, start + Duration::from_millis(250)),
+            start + Duration::from_millis(300)
+        );
+    }
+}
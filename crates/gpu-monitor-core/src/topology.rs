@@ -0,0 +1,96 @@
+//! Multi-GPU interconnect topology
+
+use serde::{Deserialize, Serialize};
+
+/// Type of connection between two GPUs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkType {
+    /// Same device
+    Same,
+    /// Connected by the given number of active NVLinks
+    NvLink(u32),
+    /// Devices on the same board (e.g., dual-GPU cards)
+    Internal,
+    /// Traverses a single PCIe switch
+    PcieSwitch,
+    /// Traverses multiple PCIe switches without a host bridge
+    PcieMultiSwitch,
+    /// Traverses a PCIe host bridge
+    HostBridge,
+    /// Traverses host bridges within a NUMA node
+    Node,
+    /// Traverses the SMP interconnect between NUMA nodes
+    System,
+    /// Topology could not be determined
+    Unknown,
+}
+
+impl LinkType {
+    /// Short label matching `nvidia-smi topo -m`
+    pub fn label(&self) -> String {
+        match self {
+            Self::Same => "X".to_string(),
+            Self::NvLink(n) => format!("NV{}", n),
+            Self::Internal => "INT".to_string(),
+            Self::PcieSwitch => "PIX".to_string(),
+            Self::PcieMultiSwitch => "PXB".to_string(),
+            Self::HostBridge => "PHB".to_string(),
+            Self::Node => "NODE".to_string(),
+            Self::System => "SYS".to_string(),
+            Self::Unknown => "?".to_string(),
+        }
+    }
+}
+
+/// Connection matrix between all GPUs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Topology {
+    /// Device indices, in matrix order
+    pub devices: Vec<u32>,
+    /// Link type between each pair of devices (`links[a][b]`)
+    pub links: Vec<Vec<LinkType>>,
+}
+
+impl Topology {
+    /// Get the link type between two devices by matrix position
+    pub fn link(&self, a: usize, b: usize) -> Option<LinkType> {
+        self.links.get(a)?.get(b).copied()
+    }
+}
+
+/// Contiguous range of CPU cores (inclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuRange {
+    /// First core in the range
+    pub first: u32,
+    /// Last core in the range
+    pub last: u32,
+}
+
+impl std::fmt::Display for CpuRange {
+    /// Format as `taskset -c` expects (`4` or `0-15`)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.first == self.last {
+            write!(f, "{}", self.first)
+        } else {
+            write!(f, "{}-{}", self.first, self.last)
+        }
+    }
+}
+
+/// Collapse a CPU bitmask (`bits_per_word` CPUs per word, lowest first) into core ranges
+pub(crate) fn cpu_ranges(mask: &[u64], bits_per_word: u32) -> Vec<CpuRange> {
+    let mut ranges: Vec<CpuRange> = Vec::new();
+    let cpus = mask.iter().enumerate().flat_map(|(word, bits)| {
+        (0..bits_per_word)
+            .filter(move |bit| bits & (1 << bit) != 0)
+            .map(move |bit| word as u32 * bits_per_word + bit)
+    });
+    for cpu in cpus {
+        match ranges.last_mut() {
+            Some(range) if range.last + 1 == cpu => range.last = cpu,
+            _ => ranges.push(CpuRange { first: cpu, last: cpu }),
+        }
+    }
+    ranges
+}
@@ -0,0 +1,150 @@
+//! XID error events from the kernel log
+//!
+//! The NVIDIA driver reports GPU faults as `NVRM: Xid` lines in the kernel
+//! log. NVML does not expose them, so they are read from `/dev/kmsg`.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read};
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Maximum number of XID events kept in memory
+const MAX_EVENTS: usize = 32;
+
+/// An XID error reported by the NVIDIA kernel driver
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XidEvent {
+    /// XID error code (e.g., 79 for "GPU has fallen off the bus")
+    pub code: u32,
+    /// PCI address as reported in the kernel log (e.g., "0000:01:00")
+    pub pci_bus_id: String,
+    /// Remainder of the log message
+    pub message: String,
+}
+
+impl XidEvent {
+    /// Parse an XID event from a kernel log message
+    ///
+    /// Expects the driver format `NVRM: Xid (PCI:0000:01:00): 79, pid=..., ...`.
+    pub fn parse(line: &str) -> Option<Self> {
+        let rest = &line[line.find("NVRM: Xid (PCI:")? + "NVRM: Xid (PCI:".len()..];
+        let (pci_bus_id, rest) = rest.split_once("): ")?;
+        let (code, message) = rest.split_once(',').unwrap_or((rest, ""));
+
+        Some(Self {
+            code: code.trim().parse().ok()?,
+            pci_bus_id: pci_bus_id.to_string(),
+            message: message.trim().to_string(),
+        })
+    }
+
+    /// Check if this event belongs to the GPU with the given NVML PCI bus ID
+    pub fn matches_bus_id(&self, pci_bus_id: &str) -> bool {
+        match (pci_key(&self.pci_bus_id), pci_key(pci_bus_id)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Normalize a PCI address to (domain, bus, device)
+///
+/// Accepts both the kernel format ("0000:01:00") and the NVML format
+/// ("00000000:01:00.0").
+fn pci_key(id: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = id.split(':');
+    let domain = u32::from_str_radix(parts.next()?, 16).ok()?;
+    let bus = u32::from_str_radix(parts.next()?, 16).ok()?;
+    let device = parts.next()?.split('.').next()?;
+    let device = u32::from_str_radix(device, 16).ok()?;
+    Some((domain, bus, device))
+}
+
+/// Incremental reader of XID events from `/dev/kmsg`
+pub(crate) struct XidWatcher {
+    kmsg: Option<File>,
+    events: Vec<XidEvent>,
+}
+
+impl XidWatcher {
+    /// Open `/dev/kmsg` for non-blocking reads
+    ///
+    /// If the kernel log is not readable (e.g., missing permissions),
+    /// the watcher stays empty.
+    pub fn new() -> Self {
+        let kmsg = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/kmsg")
+            .map_err(|e| tracing::debug!("XID events unavailable: {}", e))
+            .ok();
+
+        Self {
+            kmsg,
+            events: Vec::new(),
+        }
+    }
+
+    /// Read new kernel log records and collect XID events
+    pub fn poll(&mut self) {
+        let Some(kmsg) = self.kmsg.as_mut() else {
+            return;
+        };
+
+        // Each read returns exactly one log record
+        let mut buf = [0u8; 8192];
+        loop {
+            match kmsg.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let record = String::from_utf8_lossy(&buf[..n]);
+                    // Record format: "prio,seq,timestamp,flags;message"
+                    let message = record.split_once(';').map_or(&*record, |(_, m)| m);
+                    if let Some(event) = XidEvent::parse(message) {
+                        self.events.push(event);
+                        if self.events.len() > MAX_EVENTS {
+                            self.events.remove(0);
+                        }
+                    }
+                }
+                // Records were overwritten before we read them
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => continue,
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Get collected events for the GPU with the given PCI bus ID
+    pub fn events_for(&self, pci_bus_id: &str) -> Vec<XidEvent> {
+        self.events
+            .iter()
+            .filter(|e| e.matches_bus_id(pci_bus_id))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xid_line() {
+        let event = XidEvent::parse(
+            "NVRM: Xid (PCI:0000:01:00): 79, pid=1234, name=python, GPU has fallen off the bus.",
+        )
+        .unwrap();
+        assert_eq!(event.code, 79);
+        assert_eq!(event.pci_bus_id, "0000:01:00");
+        assert_eq!(event.message, "pid=1234, name=python, GPU has fallen off the bus.");
+
+        assert!(XidEvent::parse("usb 1-1: new high-speed USB device").is_none());
+    }
+
+    #[test]
+    fn test_matches_nvml_bus_id() {
+        let event = XidEvent::parse("NVRM: Xid (PCI:0000:0a:00): 13, Graphics Exception").unwrap();
+        assert!(event.matches_bus_id("00000000:0A:00.0"));
+        assert!(!event.matches_bus_id("00000000:01:00.0"));
+    }
+}
@@ -1,244 +1,369 @@
-import { useState, useEffect } from 'react';
-import { GpuInfo } from '../App';
-import Sparkline from './Sparkline';
-import ProcessModal from './ProcessModal';
-import ProcessList from './ProcessList';
-
-interface GpuCardProps {
-    gpu: GpuInfo;
-    mode?: 'compact' | 'expanded';
-}
-
-function GpuCard({ gpu, mode = 'compact' }: GpuCardProps) {
-    const { device, metrics, memory, processes } = gpu;
-    const [showDetails, setShowDetails] = useState(false);
-    const [searchTerm, setSearchTerm] = useState('');
-    
-    // History state for charts
-    const [loadHistory, setLoadHistory] = useState<number[]>([]);
-    const [memHistory, setMemHistory] = useState<number[]>([]);
-
-    // Update history
-    useEffect(() => {
-        setLoadHistory(prev => {
-            const next = [...prev, metrics.gpu_utilization];
-            return next.slice(-60); // Keep last 60 samples
-        });
-        
-        // Store memory usage as percentage for the chart
-        const memPercent = (memory.used / memory.total) * 100;
-        setMemHistory(prev => {
-            const next = [...prev, memPercent];
-            return next.slice(-60);
-        });
-    }, [metrics.gpu_utilization, memory.used, memory.total]);
-
-    // Calculate display values
-    const memoryUsedGiB = (memory.used / (1024 * 1024 * 1024)).toFixed(1);
-    const memoryTotalGiB = (memory.total / (1024 * 1024 * 1024)).toFixed(1);
-    const powerWatts = (metrics.power_usage / 1000).toFixed(0);
-    
-    const getTempColor = (temp: number) => {
-        if (temp > 85) return 'var(--accent-red)';
-        if (temp > 70) return 'var(--accent-orange)';
-        return 'var(--accent-green)';
-    };
-
-    // Filter processes for expanded mode
-    const filteredProcesses = processes.filter(p => 
-        p.name.toLowerCase().includes(searchTerm.toLowerCase()) ||
-        p.pid.toString().includes(searchTerm)
-    );
-
-    // --- Expanded Mode (Single GPU) ---
-    if (mode === 'expanded') {
-        return (
-            <div className="gpu-expanded">
-                <div className="expanded-header">
-                    <div className="gpu-name-large">{device.name}</div>
-                    <div className="gpu-meta-large">
-                        <span className="meta-tag">GPU {device.index}</span>
-                        <span className="meta-tag">Driver {device.driver_version}</span>
-                        <span className="meta-tag">PCI {device.pci_bus_id}</span>
-                        <span className="meta-tag">CUDA {device.cuda_version || 'N/A'}</span>
-                        <span className="meta-tag">Power Limit {device.power_limit}W</span>
-                    </div>
-                </div>
-
-                <div className="expanded-metrics-grid">
-                    {/* GPU Load Section */}
-                    <div className="expanded-metric-card">
-                        <div className="metric-header">
-                            <span className="metric-label-large">GPU Load</span>
-                            <span className="metric-value-xl">{metrics.gpu_utilization}%</span>
-                        </div>
-                        <div className="chart-container-large">
-                            <Sparkline 
-                                data={loadHistory} 
-                                color="var(--accent-blue)" 
-                                height={120}
-                                max={100}
-                            />
-                        </div>
-                    </div>
-
-                    {/* Memory Section */}
-                    <div className="expanded-metric-card">
-                        <div className="metric-header">
-                            <span className="metric-label-large">Memory</span>
-                            <div>
-                                <span className="metric-value-xl">{memoryUsedGiB}</span>
-                                <span className="metric-unit-large">/ {memoryTotalGiB} GiB</span>
-                            </div>
-                        </div>
-                        <div className="chart-container-large">
-                            <Sparkline 
-                                data={memHistory} 
-                                color="var(--accent-purple)" 
-                                height={120}
-                                max={100}
-                            />
-                        </div>
-                    </div>
-                </div>
-
-                <div className="secondary-metrics-row">
-                    <div className="stat-box">
-                        <div className="stat-label">Temperature</div>
-                        <div className="stat-value" style={{ color: getTempColor(metrics.temperature) }}>
-                            {metrics.temperature}°C
-                        </div>
-                    </div>
-                    <div className="stat-box">
-                        <div className="stat-label">Power Usage</div>
-                        <div className="stat-value">{powerWatts}W</div>
-                    </div>
-                    <div className="stat-box">
-                        <div className="stat-label">Fan Speed</div>
-                        <div className="stat-value">
-                            {metrics.fan_speed !== null ? `${metrics.fan_speed}%` : 'N/A'}
-                        </div>
-                    </div>
-                    <div className="stat-box">
-                        <div className="stat-label">Clock (Graphics)</div>
-                        <div className="stat-value">{metrics.clock_graphics} MHz</div>
-                    </div>
-                    <div className="stat-box">
-                        <div className="stat-label">Clock (Memory)</div>
-                        <div className="stat-value">{metrics.clock_memory} MHz</div>
-                    </div>
-                </div>
-
-                <div className="expanded-process-section">
-                    <div className="process-toolbar">
-                        <div className="section-title-large">Active Processes ({processes.length})</div>
-                        <input 
-                            type="text" 
-                            className="search-input"
-                            placeholder="Search processes..."
-                            value={searchTerm}
-                            onChange={(e) => setSearchTerm(e.target.value)}
-                        />
-                    </div>
-                    <div className="expanded-table-wrapper">
-                        <ProcessList processes={filteredProcesses} />
-                    </div>
-                </div>
-            </div>
-        );
-    }
-
-    // --- Compact Mode (Multi GPU) ---
-    return (
-        <>
-            <div className="gpu-card">
-                <div className="gpu-header">
-                    <div>
-                        <div className="gpu-name" title={device.name}>{device.name}</div>
-                        <div className="gpu-meta">
-                            <span className="gpu-index">GPU {device.index}</span>
-                            <span>{device.pci_bus_id}</span>
-                        </div>
-                    </div>
-                </div>
-
-                <div className="compact-metrics">
-                    {/* GPU Load Row */}
-                    <div className="metric-row">
-                        <div className="metric-info">
-                            <div className="metric-label">GPU Load</div>
-                            <div>
-                                <span className="metric-value-large">{metrics.gpu_utilization}</span>
-                                <span className="metric-unit-small">%</span>
-                            </div>
-                        </div>
-                        <div className="metric-chart">
-                            <Sparkline 
-                                data={loadHistory} 
-                                color="var(--accent-blue)" 
-                                height={40}
-                                max={100}
-                            />
-                        </div>
-                    </div>
-
-                    {/* Memory Row */}
-                    <div className="metric-row">
-                        <div className="metric-info">
-                            <div className="metric-label">Memory</div>
-                            <div>
-                                <span className="metric-value-large">{memoryUsedGiB}</span>
-                                <span className="metric-unit-small">GiB</span>
-                            </div>
-                        </div>
-                        <div className="metric-chart">
-                            <Sparkline 
-                                data={memHistory} 
-                                color="var(--accent-purple)" 
-                                height={40}
-                                max={100}
-                            />
-                        </div>
-                    </div>
-                </div>
-
-                <div className="secondary-metrics">
-                    <div className="mini-metric">
-                        <span className="mini-label">Temp</span>
-                        <span className="mini-value" style={{ color: getTempColor(metrics.temperature) }}>
-                            {metrics.temperature}°C
-                        </span>
-                    </div>
-                    <div className="mini-metric">
-                        <span className="mini-label">Power</span>
-                        <span className="mini-value">{powerWatts}W</span>
-                    </div>
-                    <div className="mini-metric">
-                        <span className="mini-label">Fan</span>
-                        <span className="mini-value">
-                            {metrics.fan_speed !== null ? `${metrics.fan_speed}%` : '-'}
-                        </span>
-                    </div>
-                </div>
-
-                <div className="card-action">
-                    <button 
-                        className="btn-details"
-                        onClick={() => setShowDetails(true)}
-                    >
-                        View Processes ({processes.length})
-                    </button>
-                </div>
-            </div>
-
-            {showDetails && (
-                <ProcessModal 
-                    gpu={gpu} 
-                    onClose={() => setShowDetails(false)} 
-                />
-            )}
-        </>
-    );
-}
-
-export default GpuCard;
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Capabilities, GpuInfo } from '../App';
+import Sparkline from './Sparkline';
+import ProcessModal from './ProcessModal';
+import ProcessList, { CommandError } from './ProcessList';
+
+interface GpuCardProps {
+    gpu: GpuInfo;
+    mode?: 'compact' | 'expanded';
+}
+
+function GpuCard({ gpu, mode = 'compact' }: GpuCardProps) {
+    const { device, metrics, memory, processes } = gpu;
+    const [showDetails, setShowDetails] = useState(false);
+    const [searchTerm, setSearchTerm] = useState('');
+    const [capabilities, setCapabilities] = useState<Capabilities | null>(null);
+
+    // Capabilities are fixed per device, so fetch them once
+    useEffect(() => {
+        invoke<Capabilities>('get_gpu_capabilities', { index: device.index })
+            .then(setCapabilities)
+            .catch(() => setCapabilities(null));
+    }, [device.index]);
+    
+    // History state for charts
+    const [loadHistory, setLoadHistory] = useState<number[]>([]);
+    const [memHistory, setMemHistory] = useState<number[]>([]);
+
+    // Update history
+    useEffect(() => {
+        setLoadHistory(prev => {
+            const next = [...prev, metrics.gpu_utilization];
+            return next.slice(-60); // Keep last 60 samples
+        });
+        
+        // Store memory usage as percentage for the chart
+        const memPercent = (memory.used / memory.total) * 100;
+        setMemHistory(prev => {
+            const next = [...prev, memPercent];
+            return next.slice(-60);
+        });
+    }, [metrics.gpu_utilization, memory.used, memory.total]);
+
+    // Calculate display values
+    const memoryUsedGiB = (memory.used / (1024 * 1024 * 1024)).toFixed(1);
+    const memoryTotalGiB = (memory.total / (1024 * 1024 * 1024)).toFixed(1);
+    const powerWatts = (metrics.power_usage / 1000).toFixed(0);
+    
+    // External power brake points at the host's power delivery, not the card
+    const powerBrake = gpu.throttle_reasons.includes('HwPowerBrakeSlowdown');
+
+    // Show every fan so one stuck fan among several is visible
+    const fanSpeed = metrics.fan_speeds.length > 1
+        ? metrics.fan_speeds.map(fan => `${fan}%`).join(' / ')
+        : metrics.fan_speed !== null ? `${metrics.fan_speed}%` : null;
+
+    const getTempColor = (temp: number) => {
+        // Color by distance to the card's slowdown threshold when it is known
+        if (device.temp_slowdown !== null) {
+            const margin = device.temp_slowdown - temp;
+            if (margin <= 5) return 'var(--accent-red)';
+            if (margin <= 15) return 'var(--accent-orange)';
+            return 'var(--accent-green)';
+        }
+        if (temp > 85) return 'var(--accent-red)';
+        if (temp > 70) return 'var(--accent-orange)';
+        return 'var(--accent-green)';
+    };
+
+    const resetGpu = async () => {
+        if (!window.confirm(`Reset GPU ${device.index} (${device.name})? Running workloads will be lost.`)) {
+            return;
+        }
+        try {
+            await invoke('reset_gpu', { index: device.index, force: false });
+        } catch (err) {
+            const error = err as CommandError;
+            if (error.kind === 'busy' &&
+                window.confirm(`${error.message}\n\nForce the reset anyway?`)) {
+                try {
+                    await invoke('reset_gpu', { index: device.index, force: true });
+                } catch (forceErr) {
+                    window.alert((forceErr as CommandError).message);
+                }
+            } else if (error.kind !== 'busy') {
+                window.alert(error.message);
+            }
+        }
+    };
+
+    const togglePersistence = async () => {
+        try {
+            await invoke('set_persistence_mode', { index: device.index, enabled: !device.persistence_mode });
+        } catch (err) {
+            window.alert((err as CommandError).message);
+        }
+    };
+
+    // Every temperature sensor, e.g. "gpu 65°C, memory 78°C"; memory limits clocks on some cards
+    const sensorSummary = Object.entries(metrics.temperatures)
+        .map(([sensor, temp]) => `${sensor} ${temp}°C`)
+        .join(', ');
+
+    // Filter processes for expanded mode
+    const filteredProcesses = processes.filter(p => 
+        p.name.toLowerCase().includes(searchTerm.toLowerCase()) ||
+        p.pid.toString().includes(searchTerm)
+    );
+
+    // Used memory not attributed to any process (driver, contexts); unknown if not collected or capped
+    const memoryOverhead = !gpu.processes_collected || processes.length < gpu.process_count
+        ? null
+        : Math.max(0, memory.used - processes.reduce((sum, p) => sum + p.gpu_memory, 0));
+
+    // --- Expanded Mode (Single GPU) ---
+    if (mode === 'expanded') {
+        return (
+            <div className="gpu-expanded">
+                <div className="expanded-header">
+                    <div className="gpu-name-large">{device.name}</div>
+                    <div className="gpu-meta-large">
+                        <span className="meta-tag">GPU {device.index}</span>
+                        <span className="meta-tag">Driver {device.driver_version}</span>
+                        <span className="meta-tag">PCI {device.pci_bus_id}</span>
+                        {device.numa_node !== null && (
+                            <span className="meta-tag">NUMA {device.numa_node}</span>
+                        )}
+                        {device.minor_number !== null && (
+                            <span className="meta-tag">/dev/nvidia{device.minor_number}</span>
+                        )}
+                        <span className="meta-tag">CUDA {device.cuda_version || 'N/A'}</span>
+                        {device.vbios_version !== null && (
+                            <span className="meta-tag">VBIOS {device.vbios_version}</span>
+                        )}
+                        <span className="meta-tag">Power Limit {device.power_limit}W</span>
+                        {device.ecc_enabled_current !== null && (
+                            <span
+                                className="meta-tag"
+                                title={device.ecc_enabled_pending !== device.ecc_enabled_current ? 'ECC change takes effect after reboot' : undefined}
+                            >
+                                ECC {device.ecc_enabled_current ? 'On' : 'Off'}
+                                {device.ecc_enabled_pending !== null &&
+                                    device.ecc_enabled_pending !== device.ecc_enabled_current &&
+                                    ` (${device.ecc_enabled_pending ? 'On' : 'Off'} after reboot)`}
+                            </span>
+                        )}
+                        {device.clock_state === 'ApplicationLocked' && (
+                            <span className="meta-tag">Locked @ {device.app_clock_graphics} MHz</span>
+                        )}
+                        {gpu.warnings.map(warning => (
+                            <span key={warning} className="meta-tag warning-tag">{warning}</span>
+                        ))}
+                        {powerBrake && (
+                            <span className="meta-tag warning-tag" title="External power brake asserted by the PSU/VRM">
+                                Power Brake
+                            </span>
+                        )}
+                        {!!metrics.pcie_replay_count && (
+                            <span className="meta-tag warning-tag" title="PCIe replays since the driver loaded">
+                                PCIe Replays {metrics.pcie_replay_count}
+                            </span>
+                        )}
+                        {capabilities?.has_nvlink && <span className="meta-tag">NVLink</span>}
+                        {capabilities?.has_mig && (
+                            <span className="meta-tag">MIG {device.mig_enabled ? 'On' : 'Off'}</span>
+                        )}
+                        {gpu.mig_instances.map(instance => (
+                            <span key={instance.uuid} className="meta-tag" title={instance.uuid}>
+                                GI {instance.gpu_instance_id} / CI {instance.compute_instance_id}: {instance.profile}
+                            </span>
+                        ))}
+                        {gpu.driving_display && (
+                            <span className="meta-tag" title="This GPU drives a display; the desktop adds baseline load">
+                                Display
+                            </span>
+                        )}
+                        {capabilities?.has_persistence_control && device.persistence_mode !== null && (
+                            <button
+                                className="meta-tag"
+                                title="Keep the driver loaded while no application uses the GPU"
+                                onClick={togglePersistence}
+                            >
+                                Persistence {device.persistence_mode ? 'On' : 'Off'}
+                            </button>
+                        )}
+                        {capabilities?.has_reset && (
+                            <button className="meta-tag danger-btn" onClick={resetGpu}>Reset GPU</button>
+                        )}
+                    </div>
+                </div>
+
+                <div className="expanded-metrics-grid">
+                    {/* GPU Load Section */}
+                    <div className="expanded-metric-card">
+                        <div className="metric-header">
+                            <span className="metric-label-large">GPU Load</span>
+                            <span className="metric-value-xl">{metrics.gpu_utilization}%</span>
+                        </div>
+                        <div className="chart-container-large">
+                            <Sparkline 
+                                data={loadHistory} 
+                                color="var(--accent-blue)" 
+                                height={120}
+                                max={100}
+                            />
+                        </div>
+                    </div>
+
+                    {/* Memory Section */}
+                    <div className="expanded-metric-card">
+                        <div className="metric-header">
+                            <span className="metric-label-large">Memory</span>
+                            <div>
+                                <span className="metric-value-xl">{memoryUsedGiB}</span>
+                                <span className="metric-unit-large">/ {memoryTotalGiB} GiB</span>
+                            </div>
+                        </div>
+                        <div className="chart-container-large">
+                            <Sparkline 
+                                data={memHistory} 
+                                color="var(--accent-purple)" 
+                                height={120}
+                                max={100}
+                            />
+                        </div>
+                    </div>
+                </div>
+
+                <div className="secondary-metrics-row">
+                    <div className="stat-box" title={sensorSummary}>
+                        <div className="stat-label">Temperature</div>
+                        <div className="stat-value" style={{ color: getTempColor(metrics.temperature) }}>
+                            {metrics.temperature}°C
+                        </div>
+                    </div>
+                    <div className="stat-box">
+                        <div className="stat-label">Power Usage</div>
+                        <div className="stat-value">{powerWatts}W</div>
+                    </div>
+                    <div className="stat-box">
+                        <div className="stat-label">Fan Speed</div>
+                        <div className="stat-value">
+                            {!device.has_fan ? 'Passive' : fanSpeed ?? 'N/A'}
+                        </div>
+                    </div>
+                    <div className="stat-box">
+                        <div className="stat-label">Clock (Graphics)</div>
+                        <div className="stat-value">{metrics.clock_graphics} MHz</div>
+                    </div>
+                    <div className="stat-box">
+                        <div className="stat-label">Clock (Memory)</div>
+                        <div className="stat-value">{metrics.clock_memory} MHz</div>
+                    </div>
+                </div>
+
+                <div className="expanded-process-section">
+                    <div className="process-toolbar">
+                        <div className="section-title-large">Active Processes ({processes.length})</div>
+                        <input 
+                            type="text" 
+                            className="search-input"
+                            placeholder="Search processes..."
+                            value={searchTerm}
+                            onChange={(e) => setSearchTerm(e.target.value)}
+                        />
+                    </div>
+                    <div className="expanded-table-wrapper">
+                        <ProcessList processes={filteredProcesses} overhead={memoryOverhead} />
+                    </div>
+                </div>
+            </div>
+        );
+    }
+
+    // --- Compact Mode (Multi GPU) ---
+    return (
+        <>
+            <div className="gpu-card">
+                <div className="gpu-header">
+                    <div>
+                        <div className="gpu-name" title={device.name}>{device.name}</div>
+                        <div className="gpu-meta">
+                            <span className="gpu-index">GPU {device.index}</span>
+                            <span>{device.pci_bus_id}</span>
+                        </div>
+                    </div>
+                </div>
+
+                <div className="compact-metrics">
+                    {/* GPU Load Row */}
+                    <div className="metric-row">
+                        <div className="metric-info">
+                            <div className="metric-label">GPU Load</div>
+                            <div>
+                                <span className="metric-value-large">{metrics.gpu_utilization}</span>
+                                <span className="metric-unit-small">%</span>
+                            </div>
+                        </div>
+                        <div className="metric-chart">
+                            <Sparkline 
+                                data={loadHistory} 
+                                color="var(--accent-blue)" 
+                                height={40}
+                                max={100}
+                            />
+                        </div>
+                    </div>
+
+                    {/* Memory Row */}
+                    <div className="metric-row">
+                        <div className="metric-info">
+                            <div className="metric-label">Memory</div>
+                            <div>
+                                <span className="metric-value-large">{memoryUsedGiB}</span>
+                                <span className="metric-unit-small">GiB</span>
+                            </div>
+                        </div>
+                        <div className="metric-chart">
+                            <Sparkline 
+                                data={memHistory} 
+                                color="var(--accent-purple)" 
+                                height={40}
+                                max={100}
+                            />
+                        </div>
+                    </div>
+                </div>
+
+                <div className="secondary-metrics">
+                    <div className="mini-metric">
+                        <span className="mini-label">Temp</span>
+                        <span className="mini-value" style={{ color: getTempColor(metrics.temperature) }}>
+                            {metrics.temperature}°C
+                        </span>
+                    </div>
+                    <div className="mini-metric">
+                        <span className="mini-label">Power</span>
+                        <span className="mini-value">{powerWatts}W</span>
+                    </div>
+                    <div className="mini-metric">
+                        <span className="mini-label">Fan</span>
+                        <span className="mini-value">
+                            {!device.has_fan ? 'passive' : fanSpeed ?? '-'}
+                        </span>
+                    </div>
+                </div>
+
+                <div className="card-action">
+                    <button 
+                        className="btn-details"
+                        onClick={() => setShowDetails(true)}
+                    >
+                        View Processes ({processes.length})
+                    </button>
+                </div>
+            </div>
+
+            {showDetails && (
+                <ProcessModal 
+                    gpu={gpu} 
+                    onClose={() => setShowDetails(false)} 
+                />
+            )}
+        </>
+    );
+}
+
+export default GpuCard;
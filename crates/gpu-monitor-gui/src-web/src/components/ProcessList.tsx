@@ -1,61 +1,120 @@
-interface GpuProcess {
-    pid: number;
-    name: string;
-    gpu_memory: number;
-    process_type: 'Graphics' | 'Compute' | 'Mixed' | 'Unknown';
-}
-
-interface ProcessListProps {
-    processes: GpuProcess[];
-}
-
-function ProcessList({ processes }: ProcessListProps) {
-    const formatMemory = (bytes: number) => {
-        const mib = bytes / (1024 * 1024);
-        return `${mib.toFixed(0)} MiB`;
-    };
-
-    const getTypeTag = (type: string) => {
-        switch (type) {
-            case 'Graphics': return <span className="tag gfx">Graphics</span>;
-            case 'Compute': return <span className="tag comp">Compute</span>;
-            case 'Mixed': return <span className="tag">Mixed</span>;
-            default: return <span className="tag">Unknown</span>;
-        }
-    };
-
-    return (
-        <div className="table-container">
-            <table className="process-table">
-                <thead>
-                <tr>
-                    <th className="col-pid">PID</th>
-                    <th>Process Name</th>
-                    <th className="col-type">Type</th>
-                    <th className="col-mem">Memory</th>
-                </tr>
-                </thead>
-                <tbody>
-                {processes.length === 0 ? (
-                    <tr>
-                        <td colSpan={4} style={{ textAlign: 'center', color: 'var(--text-secondary)', padding: '32px' }}>
-                            No processes found
-                        </td>
-                    </tr>
-                ) : (
-                    processes.map((proc) => (
-                        <tr key={proc.pid}>
-                            <td className="col-pid">{proc.pid}</td>
-                            <td>{proc.name}</td>
-                            <td className="col-type">{getTypeTag(proc.process_type)}</td>
-                            <td className="col-mem">{formatMemory(proc.gpu_memory)}</td>
-                        </tr>
-                    ))
-                )}
-                </tbody>
-            </table>
-        </div>
-    );
-}
-
-export default ProcessList;
+import { invoke } from '@tauri-apps/api/core';
+
+interface GpuProcess {
+    pid: number;
+    name: string;
+    user: string;
+    cmdline: string | null;
+    gpu_memory: number;
+    process_type: 'Graphics' | 'Compute' | 'Mixed' | 'Unknown';
+    active: boolean;
+    sm_utilization: number | null;
+    runtime_seconds: number | null;
+}
+
+export interface CommandError {
+    message: string;
+    kind: 'permission_denied' | 'not_found' | 'busy' | 'other';
+}
+
+interface ProcessListProps {
+    processes: GpuProcess[];
+    /** Used memory not attributed to any process, null if unknown */
+    overhead: number | null;
+}
+
+function ProcessList({ processes, overhead }: ProcessListProps) {
+    const formatMemory = (bytes: number) => {
+        const mib = bytes / (1024 * 1024);
+        return `${mib.toFixed(0)} MiB`;
+    };
+
+    const formatRuntime = (seconds: number | null) => {
+        if (seconds === null) return '-';
+        const days = Math.floor(seconds / 86400);
+        const hours = Math.floor(seconds / 3600) % 24;
+        const minutes = Math.floor(seconds / 60) % 60;
+        if (days > 0) return `${days}d ${hours}h`;
+        if (hours > 0) return `${hours}h ${minutes}m`;
+        if (minutes > 0) return `${minutes}m ${seconds % 60}s`;
+        return `${seconds}s`;
+    };
+
+    const killProcess = async (proc: GpuProcess) => {
+        if (!window.confirm(`Terminate ${proc.name} (PID ${proc.pid})?`)) {
+            return;
+        }
+        try {
+            await invoke('kill_gpu_process', { pid: proc.pid });
+        } catch (err) {
+            const error = err as CommandError;
+            if (error.kind === 'permission_denied') {
+                window.alert(`Permission denied: ${proc.name} belongs to another user.`);
+            } else if (error.kind !== 'not_found') {
+                window.alert(error.message);
+            }
+        }
+    };
+
+    const getTypeTag = (type: string) => {
+        switch (type) {
+            case 'Graphics': return <span className="tag gfx">Graphics</span>;
+            case 'Compute': return <span className="tag comp">Compute</span>;
+            case 'Mixed': return <span className="tag">Mixed</span>;
+            default: return <span className="tag">Unknown</span>;
+        }
+    };
+
+    return (
+        <div className="table-container">
+            <table className="process-table">
+                <thead>
+                <tr>
+                    <th className="col-pid">PID</th>
+                    <th>Process Name</th>
+                    <th className="col-type">Type</th>
+                    <th className="col-mem">Memory</th>
+                    <th className="col-time">Runtime</th>
+                    <th className="col-action"></th>
+                </tr>
+                </thead>
+                <tbody>
+                {processes.length === 0 ? (
+                    <tr>
+                        <td colSpan={6} style={{ textAlign: 'center', color: 'var(--text-secondary)', padding: '32px' }}>
+                            No processes found
+                        </td>
+                    </tr>
+                ) : (
+                    processes.map((proc) => (
+                        <tr key={proc.pid}>
+                            <td className="col-pid">{proc.pid}</td>
+                            <td>{proc.name}</td>
+                            <td className="col-type">{getTypeTag(proc.process_type)}</td>
+                            <td className="col-mem">{formatMemory(proc.gpu_memory)}</td>
+                            <td className="col-time">{formatRuntime(proc.runtime_seconds)}</td>
+                            <td className="col-action">
+                                <button className="kill-btn" title="Terminate process" onClick={() => killProcess(proc)}>
+                                    ✕
+                                </button>
+                            </td>
+                        </tr>
+                    ))
+                )}
+                {overhead !== null && overhead > 0 && (
+                    <tr className="overhead-row" title="Memory used by the driver and CUDA contexts rather than a listed process">
+                        <td className="col-pid">-</td>
+                        <td>other/reserved</td>
+                        <td className="col-type"></td>
+                        <td className="col-mem">{formatMemory(overhead)}</td>
+                        <td className="col-time"></td>
+                        <td className="col-action"></td>
+                    </tr>
+                )}
+                </tbody>
+            </table>
+        </div>
+    );
+}
+
+export default ProcessList;
@@ -1,17 +1,17 @@
-import { defineConfig } from 'vite'
-import react from '@vitejs/plugin-react'
-
-export default defineConfig({
-    plugins: [react()],
-    clearScreen: false,
-    server: {
-        port: 5173,
-        strictPort: true,
-    },
-    envPrefix: ['VITE_', 'TAURI_'],
-    build: {
-        target: 'esnext',
-        minify: !process.env.TAURI_DEBUG ? 'esbuild' : false,
-        sourcemap: !!process.env.TAURI_DEBUG,
-    },
-})
+import { defineConfig } from 'vite'
+import react from '@vitejs/plugin-react'
+
+export default defineConfig({
+    plugins: [react()],
+    clearScreen: false,
+    server: {
+        port: 5173,
+        strictPort: true,
+    },
+    envPrefix: ['VITE_', 'TAURI_'],
+    build: {
+        target: 'esnext',
+        minify: !process.env.TAURI_DEBUG ? 'esbuild' : false,
+        sourcemap: !!process.env.TAURI_DEBUG,
+    },
+})
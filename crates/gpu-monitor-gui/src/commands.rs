@@ -1,74 +1,231 @@
-//! Tauri IPC commands for GPU monitoring
-
-use gpu_monitor_core::{GpuInfo, GpuMonitor};
-use serde::Serialize;
-use std::sync::Mutex;
-use tauri::State;
-
-/// Application state holding the GPU monitor instance
-pub struct AppState {
-    pub monitor: Mutex<Option<GpuMonitor>>,
-}
-
-impl Default for AppState {
-    fn default() -> Self {
-        Self {
-            monitor: Mutex::new(GpuMonitor::new().ok()),
-        }
-    }
-}
-
-/// Error response for IPC commands
-#[derive(Debug, Serialize)]
-pub struct CommandError {
-    pub message: String,
-}
-
-impl From<gpu_monitor_core::Error> for CommandError {
-    fn from(err: gpu_monitor_core::Error) -> Self {
-        Self {
-            message: err.to_string(),
-        }
-    }
-}
-
-/// Get all GPU information
-#[tauri::command]
-pub fn get_gpu_info(state: State<AppState>) -> Result<Vec<GpuInfo>, CommandError> {
-    let guard = state.monitor.lock().map_err(|e| CommandError {
-        message: format!("Failed to acquire lock: {}", e),
-    })?;
-
-    match guard.as_ref() {
-        Some(monitor) => monitor.get_all_gpu_info().map_err(|e| e.into()),
-        None => Err(CommandError {
-            message: "GPU monitor not initialized. Make sure NVIDIA drivers are installed."
-                .to_string(),
-        }),
-    }
-}
-
-/// Get GPU count
-#[tauri::command]
-pub fn get_gpu_count(state: State<AppState>) -> Result<u32, CommandError> {
-    let guard = state.monitor.lock().map_err(|e| CommandError {
-        message: format!("Failed to acquire lock: {}", e),
-    })?;
-
-    match guard.as_ref() {
-        Some(monitor) => monitor.device_count().map_err(|e| e.into()),
-        None => Err(CommandError {
-            message: "GPU monitor not initialized".to_string(),
-        }),
-    }
-}
-
-/// Check if GPU monitoring is available
-#[tauri::command]
-pub fn is_gpu_available(state: State<AppState>) -> bool {
-    let guard = state.monitor.lock();
-    match guard {
-        Ok(g) => g.is_some(),
-        Err(_) => false,
-    }
-}
+//! Tauri IPC commands for GPU monitoring
+
+use gpu_monitor_core::{Capabilities, GpuInfo, GpuMonitor, GpuProcess, ProcessMemoryTree};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::State;
+
+/// Application state holding the GPU monitor instance
+pub struct AppState {
+    pub monitor: Mutex<Option<GpuMonitor>>,
+    /// Capabilities per GPU index, which do not change at runtime
+    pub capabilities: Mutex<HashMap<u32, Capabilities>>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self {
+            monitor: Mutex::new(GpuMonitor::new().ok()),
+            capabilities: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// Error response for IPC commands
+#[derive(Debug, Serialize)]
+pub struct CommandError {
+    pub message: String,
+    pub kind: ErrorKind,
+}
+
+/// Error category the frontend can react to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Insufficient privileges for the operation
+    PermissionDenied,
+    /// Target process does not exist
+    NotFound,
+    /// GPU is in use by processes or a display
+    Busy,
+    /// Any other failure
+    Other,
+}
+
+impl CommandError {
+    /// Create an uncategorized error
+    fn other(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ErrorKind::Other,
+        }
+    }
+}
+
+impl From<gpu_monitor_core::Error> for CommandError {
+    fn from(err: gpu_monitor_core::Error) -> Self {
+        use gpu_monitor_core::Error;
+        let kind = match err {
+            Error::PermissionDenied(_) | Error::ResetDenied(_) | Error::PersistenceDenied(_) => {
+                ErrorKind::PermissionDenied
+            }
+            Error::GpuBusy(_) => ErrorKind::Busy,
+            Error::ProcessNotFound(_) => ErrorKind::NotFound,
+            _ => ErrorKind::Other,
+        };
+        Self {
+            message: err.to_string(),
+            kind,
+        }
+    }
+}
+
+/// Get all GPU information
+#[tauri::command]
+pub fn get_gpu_info(state: State<AppState>) -> Result<Vec<GpuInfo>, CommandError> {
+    let guard = state
+        .monitor
+        .lock()
+        .map_err(|e| CommandError::other(format!("Failed to acquire lock: {}", e)))?;
+
+    match guard.as_ref() {
+        Some(monitor) => monitor.get_all_gpu_info().map_err(|e| e.into()),
+        None => Err(CommandError::other(
+            "GPU monitor not initialized. Make sure NVIDIA drivers are installed.",
+        )),
+    }
+}
+
+/// Get the optional features of a GPU so the frontend only offers working controls
+///
+/// Cached after the first call, so later calls do not wait for the monitor lock.
+#[tauri::command]
+pub fn get_gpu_capabilities(state: State<AppState>, index: u32) -> Result<Capabilities, CommandError> {
+    let cached = state
+        .capabilities
+        .lock()
+        .map_err(|e| CommandError::other(format!("Failed to acquire lock: {}", e)))?
+        .get(&index)
+        .copied();
+    if let Some(caps) = cached {
+        return Ok(caps);
+    }
+
+    let guard = state
+        .monitor
+        .lock()
+        .map_err(|e| CommandError::other(format!("Failed to acquire lock: {}", e)))?;
+
+    let caps = match guard.as_ref() {
+        Some(monitor) => monitor.capabilities(index)?,
+        None => return Err(CommandError::other("GPU monitor not initialized")),
+    };
+    if let Ok(mut cache) = state.capabilities.lock() {
+        cache.insert(index, caps);
+    }
+    Ok(caps)
+}
+
+/// Get process memory of a GPU grouped by user for the treemap view
+#[tauri::command]
+pub fn get_process_memory_tree(
+    state: State<AppState>,
+    index: u32,
+) -> Result<ProcessMemoryTree, CommandError> {
+    let guard = state
+        .monitor
+        .lock()
+        .map_err(|e| CommandError::other(format!("Failed to acquire lock: {}", e)))?;
+
+    match guard.as_ref() {
+        Some(monitor) => monitor.process_memory_tree(index).map_err(|e| e.into()),
+        None => Err(CommandError::other("GPU monitor not initialized")),
+    }
+}
+
+/// List processes using a GPU
+#[tauri::command]
+pub fn list_gpu_processes(
+    state: State<AppState>,
+    index: u32,
+) -> Result<Vec<GpuProcess>, CommandError> {
+    let guard = state
+        .monitor
+        .lock()
+        .map_err(|e| CommandError::other(format!("Failed to acquire lock: {}", e)))?;
+
+    match guard.as_ref() {
+        Some(monitor) => monitor.get_processes(index).map_err(|e| e.into()),
+        None => Err(CommandError::other("GPU monitor not initialized")),
+    }
+}
+
+/// Terminate a GPU process
+///
+/// The frontend must ask the user for confirmation before calling this.
+#[tauri::command]
+pub fn kill_gpu_process(state: State<AppState>, pid: u32) -> Result<(), CommandError> {
+    let guard = state
+        .monitor
+        .lock()
+        .map_err(|e| CommandError::other(format!("Failed to acquire lock: {}", e)))?;
+
+    match guard.as_ref() {
+        Some(monitor) => monitor.kill_process(pid).map_err(|e| e.into()),
+        None => Err(CommandError::other("GPU monitor not initialized")),
+    }
+}
+
+/// Reset a GPU
+///
+/// The frontend must ask the user for confirmation before calling this.
+#[tauri::command]
+pub fn reset_gpu(state: State<AppState>, index: u32, force: bool) -> Result<(), CommandError> {
+    let guard = state
+        .monitor
+        .lock()
+        .map_err(|e| CommandError::other(format!("Failed to acquire lock: {}", e)))?;
+
+    match guard.as_ref() {
+        Some(monitor) => monitor.reset_gpu(index, force)?,
+        None => return Err(CommandError::other("GPU monitor not initialized")),
+    }
+    // A MIG mode change applied by the reset changes the available controls
+    if let Ok(mut cache) = state.capabilities.lock() {
+        cache.remove(&index);
+    }
+    Ok(())
+}
+
+/// Enable or disable persistence mode on a GPU
+#[tauri::command]
+pub fn set_persistence_mode(
+    state: State<AppState>,
+    index: u32,
+    enabled: bool,
+) -> Result<(), CommandError> {
+    let guard = state
+        .monitor
+        .lock()
+        .map_err(|e| CommandError::other(format!("Failed to acquire lock: {}", e)))?;
+
+    match guard.as_ref() {
+        Some(monitor) => monitor.set_persistence_mode(index, enabled).map_err(|e| e.into()),
+        None => Err(CommandError::other("GPU monitor not initialized")),
+    }
+}
+
+/// Get GPU count
+#[tauri::command]
+pub fn get_gpu_count(state: State<AppState>) -> Result<u32, CommandError> {
+    let guard = state
+        .monitor
+        .lock()
+        .map_err(|e| CommandError::other(format!("Failed to acquire lock: {}", e)))?;
+
+    match guard.as_ref() {
+        Some(monitor) => monitor.device_count().map_err(|e| e.into()),
+        None => Err(CommandError::other("GPU monitor not initialized")),
+    }
+}
+
+/// Check if GPU monitoring is available
+#[tauri::command]
+pub fn is_gpu_available(state: State<AppState>) -> bool {
+    let guard = state.monitor.lock();
+    match guard {
+        Ok(g) => g.is_some(),
+        Err(_) => false,
+    }
+}
@@ -1,5 +1,5 @@
-//! GPU Monitor GUI - Tauri application library
-
-mod commands;
-
-pub use commands::*;
+//! GPU Monitor GUI - Tauri application library
+
+mod commands;
+
+pub use commands::*;
@@ -1,18 +1,27 @@
-//! GPU Monitor GUI - Tauri main entry point
-
-#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
-
-mod commands;
-use commands::{get_gpu_count, get_gpu_info, is_gpu_available, AppState};
-
-fn main() {
-    tauri::Builder::default()
-        .manage(AppState::default())
-        .invoke_handler(tauri::generate_handler![
-            get_gpu_info,
-            get_gpu_count,
-            is_gpu_available
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
+//! GPU Monitor GUI - Tauri main entry point
+
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+
+mod commands;
+use commands::{
+    get_gpu_capabilities, get_gpu_count, get_gpu_info, get_process_memory_tree, is_gpu_available, kill_gpu_process,
+    list_gpu_processes, reset_gpu, set_persistence_mode, AppState,
+};
+
+fn main() {
+    tauri::Builder::default()
+        .manage(AppState::default())
+        .invoke_handler(tauri::generate_handler![
+            get_gpu_info,
+            get_gpu_count,
+            get_gpu_capabilities,
+            get_process_memory_tree,
+            list_gpu_processes,
+            kill_gpu_process,
+            reset_gpu,
+            set_persistence_mode,
+            is_gpu_available
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}